@@ -0,0 +1,219 @@
+//! Compile time image conversion for the `ssd1351` driver crate
+//!
+//! Use the macros through the `macros` feature of `ssd1351`, which re-exports them.
+
+extern crate proc_macro;
+
+use std::fs::File;
+use std::path::PathBuf;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Literal, Span};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{parse_macro_input, Ident, LitStr, Token};
+
+/// Output format of an embedded image
+enum Format {
+    /// Big endian Rgb565 pixels, row by row
+    Rgb565Be,
+    /// Runs of a count and a big endian Rgb565 colour
+    Rle,
+}
+
+/// Arguments of `include_image!`
+struct Args {
+    path: LitStr,
+    format: Format,
+    size: (u32, u32),
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        let mut format = Format::Rgb565Be;
+        let mut size = (128, 128);
+
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let value: Ident = input.parse()?;
+            match (key.to_string().as_str(), value.to_string().as_str()) {
+                ("format", "rgb565be") => format = Format::Rgb565Be,
+                ("format", "rle") => format = Format::Rle,
+                ("format", _) => {
+                    return Err(syn::Error::new(
+                        value.span(),
+                        "expected `rgb565be` or `rle`",
+                    ));
+                }
+                ("size", "Display128x128") => size = (128, 128),
+                ("size", "Display128x96") => size = (128, 96),
+                ("size", _) => {
+                    return Err(syn::Error::new(
+                        value.span(),
+                        "expected `Display128x128` or `Display128x96`",
+                    ));
+                }
+                _ => {
+                    return Err(syn::Error::new(key.span(), "expected `format` or `size`"));
+                }
+            }
+        }
+
+        Ok(Args { path, format, size })
+    }
+}
+
+/// Decode a PNG file into 8 bit red, green and blue pixels and its dimensions
+fn decode_png(path: &PathBuf) -> Result<(Vec<[u8; 3]>, u32, u32), String> {
+    let file = File::open(path).map_err(|e| format!("can't open {}: {}", path.display(), e))?;
+    let mut decoder = png::Decoder::new(file);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder
+        .read_info()
+        .map_err(|e| format!("can't decode {}: {}", path.display(), e))?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buf)
+        .map_err(|e| format!("can't decode {}: {}", path.display(), e))?;
+    let buf = &buf[..info.buffer_size()];
+
+    let pixels = match info.color_type {
+        png::ColorType::Rgb => buf.chunks_exact(3).map(|p| [p[0], p[1], p[2]]).collect(),
+        png::ColorType::Rgba => buf.chunks_exact(4).map(|p| [p[0], p[1], p[2]]).collect(),
+        png::ColorType::Grayscale => buf.iter().map(|&p| [p; 3]).collect(),
+        png::ColorType::GrayscaleAlpha => buf.chunks_exact(2).map(|p| [p[0]; 3]).collect(),
+        png::ColorType::Indexed => return Err(format!("{}: unexpanded palette", path.display())),
+    };
+    Ok((pixels, info.width, info.height))
+}
+
+/// Reduce 8 bit channels to a raw Rgb565 value by dropping their low bits, like
+/// `ssd1351::dither::to_rgb565` with `Dithering::None` does at runtime
+fn to_rgb565([r, g, b]: [u8; 3]) -> u16 {
+    ((r as u16 >> 3) << 11) | ((g as u16 >> 2) << 5) | (b as u16 >> 3)
+}
+
+/// Check an image fits on a display in either orientation, so images for displays rotated by
+/// 90º or 270º fit as well
+fn fits((width, height): (u32, u32), (max_width, max_height): (u32, u32)) -> bool {
+    (width <= max_width && height <= max_height) || (width <= max_height && height <= max_width)
+}
+
+/// Run length encode Rgb565 pixels like `ssd1351::rle::encode`
+fn encode_rle(pixels: &[u16]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut rest = pixels;
+    while let Some(&color) = rest.first() {
+        let count = rest
+            .iter()
+            .take(u8::MAX as usize)
+            .take_while(|&&pixel| pixel == color)
+            .count();
+        out.push(count as u8);
+        out.extend_from_slice(&color.to_be_bytes());
+        rest = &rest[count..];
+    }
+    out
+}
+
+/// Convert a PNG image at compile time into data for the `ssd1351` blit and sprite APIs.
+///
+/// The path is relative to the manifest directory of the crate using the macro. The image
+/// must fit on the display, 128x128 pixels by default, in either orientation so images for
+/// displays rotated by 90º or 270º fit as well:
+///
+/// ```ignore
+/// static LOGO: ssd1351::image::Image = ssd1351::include_image!("logo.png");
+/// static BANNER: ssd1351::image::Image =
+///     ssd1351::include_image!("banner.png", format = rgb565be, size = Display128x96);
+/// static SPLASH: ssd1351::rle::RleImage = ssd1351::include_image!("splash.png", format = rle);
+/// ```
+///
+/// `format = rgb565be` gives an `ssd1351::image::Image` of big endian Rgb565 pixels,
+/// `format = rle` a run length encoded `ssd1351::rle::RleImage`. Alpha channels are dropped and
+/// colours are reduced like `ssd1351::dither::to_rgb565` without dithering.
+#[proc_macro]
+pub fn include_image(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input as Args);
+    match expand(&args) {
+        Ok(tokens) => tokens.into(),
+        Err(message) => syn::Error::new(args.path.span(), message)
+            .to_compile_error()
+            .into(),
+    }
+}
+
+fn expand(args: &Args) -> Result<proc_macro2::TokenStream, String> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").map_err(|e| e.to_string())?;
+    let path = PathBuf::from(manifest_dir).join(args.path.value());
+    let (pixels, width, height) = decode_png(&path)?;
+
+    let (max_width, max_height) = args.size;
+    if !fits((width, height), args.size) {
+        return Err(format!(
+            "{}x{} image does not fit on a {}x{} display in either orientation",
+            width, height, max_width, max_height
+        ));
+    }
+
+    // Rebuild when the image changes
+    let path = path.to_string_lossy().into_owned();
+    let track = quote! { const _: &[u8] = include_bytes!(#path); };
+
+    let pixels: Vec<u16> = pixels.into_iter().map(to_rgb565).collect();
+    let (data, image) = match args.format {
+        Format::Rgb565Be => (
+            pixels
+                .iter()
+                .flat_map(|pixel| pixel.to_be_bytes())
+                .collect(),
+            quote! { ::ssd1351::image::Image },
+        ),
+        Format::Rle => (encode_rle(&pixels), quote! { ::ssd1351::rle::RleImage }),
+    };
+    let mut data = Literal::byte_string(&data);
+    data.set_span(Span::call_site());
+
+    Ok(quote! {
+        {
+            #track
+            #image::new(#width, #height, #data)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ssd1351::dither::Dithering;
+
+    #[test]
+    fn fits_either_orientation() {
+        assert!(fits((128, 96), (128, 96)));
+        assert!(fits((96, 128), (128, 96)));
+        assert!(fits((20, 100), (128, 96)));
+        assert!(!fits((128, 128), (128, 96)));
+        assert!(!fits((100, 100), (128, 96)));
+        assert!(!fits((129, 1), (128, 128)));
+    }
+
+    #[test]
+    fn conversion_matches_runtime() {
+        for value in 0..=255u8 {
+            for rgb in [[value, 0, 0], [0, value, 0], [0, 0, value], [value; 3]] {
+                assert_eq!(
+                    to_rgb565(rgb),
+                    ssd1351::dither::to_rgb565(rgb, 0, 0, Dithering::None),
+                    "{:?}",
+                    rgb
+                );
+            }
+        }
+    }
+}
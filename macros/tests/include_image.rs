@@ -0,0 +1,38 @@
+//! Expansion of `include_image!` against a 3x2 fixture: red, red and green on the first row,
+//! green, blue and 0x123456 on the second
+
+use ssd1351::image::Image;
+use ssd1351::rle::RleImage;
+use ssd1351_macros::include_image;
+
+const RED: u16 = 0xF800;
+const GREEN: u16 = 0x07E0;
+const BLUE: u16 = 0x001F;
+/// 0x123456 with the low bits of every channel dropped
+const DARK: u16 = 0x11AA;
+
+const PIXELS: [u16; 6] = [RED, RED, GREEN, GREEN, BLUE, DARK];
+
+static RGB565: Image = include_image!("testdata/pixels.png", format = rgb565be);
+static RLE: RleImage = include_image!("testdata/pixels.png", format = rle, size = Display128x96);
+
+#[test]
+fn rgb565be() {
+    assert_eq!((RGB565.width(), RGB565.height()), (3, 2));
+    let pixels: Vec<u16> = RGB565
+        .data()
+        .chunks_exact(2)
+        .map(|pixel| u16::from_be_bytes([pixel[0], pixel[1]]))
+        .collect();
+    assert_eq!(pixels, PIXELS);
+}
+
+#[test]
+fn rle() {
+    assert_eq!((RLE.width(), RLE.height()), (3, 2));
+    assert_eq!(
+        RLE.data(),
+        [2, 0xF8, 0x00, 2, 0x07, 0xE0, 1, 0x00, 0x1F, 1, 0x11, 0xAA]
+    );
+    assert!(RLE.pixels().eq(PIXELS));
+}
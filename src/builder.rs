@@ -1,18 +1,30 @@
 //! Interface factory
 
 use super::display::Display;
+use super::error::Error;
 use super::mode::displaymode::DisplayMode;
 use super::mode::raw::RawMode;
+use super::properties::DataBus;
 use super::properties::DisplayRotation;
 use super::properties::DisplaySize;
+use super::properties::GpioMode;
+use super::properties::ResetTiming;
 
 use display_interface::WriteOnlyDataCommand;
+use hal::delay::DelayNs;
+use hal::digital::OutputPin;
 
 /// Builder struct. Driver options and interface are set using its methods.
 #[derive(Clone)]
 pub struct Builder {
     display_size: DisplaySize,
     rotation: DisplayRotation,
+    data_bus: DataBus,
+    gpio0: GpioMode,
+    gpio1: GpioMode,
+    reset_timing: ResetTiming,
+    brightness: u8,
+    channel_contrast: (u8, u8, u8),
 }
 
 impl Default for Builder {
@@ -22,11 +34,19 @@ impl Default for Builder {
 }
 
 impl Builder {
-    /// Create new builder with a default size of 128 x 128 pixels and no rotation.
+    /// Create new builder with a default size of 128 x 128 pixels, no rotation, a serial data
+    /// bus, both GPIO pins high impedance with their inputs disabled and the default
+    /// [ResetTiming].
     pub fn new() -> Self {
         Self {
             display_size: DisplaySize::Display128x128,
             rotation: DisplayRotation::Rotate0,
+            data_bus: DataBus::Serial,
+            gpio0: GpioMode::HiZInputDisabled,
+            gpio1: GpioMode::HiZInputDisabled,
+            reset_timing: ResetTiming::default(),
+            brightness: 255,
+            channel_contrast: (0xC8, 0x8F, 0xC8),
         }
     }
 
@@ -45,6 +65,62 @@ impl Builder {
         Self { rotation, ..*self }
     }
 
+    /// Set the data bus the display is connected to. Defaults to [DataBus::Serial], which covers
+    /// [SPIInterface](crate::prelude::SPIInterface). Use [DataBus::Parallel8Bit] or
+    /// [DataBus::Parallel16Bit] with a 6800 or 8080 parallel interface such as the ones provided
+    /// by `display-interface-parallel-gpio`, so the controller is configured for the bus width and
+    /// pixel data is sent in the matching word size.
+    pub fn with_data_bus(&self, data_bus: DataBus) -> Self {
+        Self { data_bus, ..*self }
+    }
+
+    /// Set the modes the GPIO0 and GPIO1 pins of the controller are put in when the display is
+    /// initialised. Both default to [GpioMode::HiZInputDisabled].
+    pub fn with_gpio(&self, gpio0: GpioMode, gpio1: GpioMode) -> Self {
+        Self {
+            gpio0,
+            gpio1,
+            ..*self
+        }
+    }
+
+    /// Set the brightness the display is initialised with, from 0 to 255. Defaults to 255.
+    pub fn with_brightness(&self, brightness: u8) -> Self {
+        Self {
+            brightness,
+            ..*self
+        }
+    }
+
+    /// Set the red, green and blue contrast values at full brightness, e.g. from a white point
+    /// calibration of the panel. Defaults to 0xC8, 0x8F and 0xC8.
+    pub fn with_channel_contrast(&self, red: u8, green: u8, blue: u8) -> Self {
+        Self {
+            channel_contrast: (red, green, blue),
+            ..*self
+        }
+    }
+
+    /// Set the timings of the hardware reset sequence
+    pub fn with_reset_timing(&self, reset_timing: ResetTiming) -> Self {
+        Self {
+            reset_timing,
+            ..*self
+        }
+    }
+
+    /// Create the display with all builder options applied
+    fn display<DI>(&self, display_interface: DI) -> Display<DI>
+    where
+        DI: WriteOnlyDataCommand,
+    {
+        Display::new(display_interface, self.display_size, self.rotation)
+            .with_reset_timing(self.reset_timing)
+            .with_brightness(self.brightness, self.channel_contrast)
+            .with_data_bus(self.data_bus)
+            .with_gpio(self.gpio0, self.gpio1)
+    }
+
     #[cfg(feature = "buffered")]
     /// Finish the builder and use the given interface to communicate with the display
     pub fn connect_interface<DI>(
@@ -56,7 +132,7 @@ impl Builder {
         DI: WriteOnlyDataCommand,
     {
         assert_eq!(buffer.len(), self.display_size.num_pixels() * 2);
-        let properties = Display::new(display_interface, self.display_size, self.rotation);
+        let properties = self.display(display_interface);
         DisplayMode::<RawMode<DI>>::new(properties, buffer)
     }
 
@@ -66,7 +142,47 @@ impl Builder {
     where
         DI: WriteOnlyDataCommand,
     {
-        let properties = Display::new(display_interface, self.display_size, self.rotation);
+        let properties = self.display(display_interface);
         DisplayMode::<RawMode<DI>>::new(properties)
     }
+
+    #[cfg(feature = "buffered")]
+    /// Finish the builder, reset the display with the given pin and initialise it. The pin is kept
+    /// by the display for later calls to `reinit`.
+    pub fn connect_interface_with_reset<DI, RST, DELAY>(
+        &self,
+        display_interface: DI,
+        rst: RST,
+        delay: &mut DELAY,
+        buffer: &'static mut [u8],
+    ) -> Result<DisplayMode<RawMode<DI, RST>>, Error>
+    where
+        DI: WriteOnlyDataCommand,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        assert_eq!(buffer.len(), self.display_size.num_pixels() * 2);
+        let mut properties = self.display(display_interface).with_reset_pin(rst);
+        properties.reinit(delay)?;
+        Ok(DisplayMode::<RawMode<DI, RST>>::new(properties, buffer))
+    }
+
+    #[cfg(not(feature = "buffered"))]
+    /// Finish the builder, reset the display with the given pin and initialise it. The pin is kept
+    /// by the display for later calls to `reinit`.
+    pub fn connect_interface_with_reset<DI, RST, DELAY>(
+        &self,
+        display_interface: DI,
+        rst: RST,
+        delay: &mut DELAY,
+    ) -> Result<DisplayMode<RawMode<DI, RST>>, Error>
+    where
+        DI: WriteOnlyDataCommand,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        let mut properties = self.display(display_interface).with_reset_pin(rst);
+        properties.reinit(delay)?;
+        Ok(DisplayMode::<RawMode<DI, RST>>::new(properties))
+    }
 }
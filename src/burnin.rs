@@ -0,0 +1,133 @@
+//! OLED burn-in mitigation
+
+/// A periodic shift of the whole image by up to `amplitude` pixels in either direction, to
+/// spread the wear of static UI elements over neighbouring pixels.
+///
+/// The offsets walk a `2 * amplitude + 1` pixels wide square around the original position row by
+/// row, so the image spends the same time at every offset and its average position stays fixed.
+/// Apply it with
+/// [`GraphicsMode::update_pixel_shift`](../mode/graphics/struct.GraphicsMode.html#method.update_pixel_shift).
+#[derive(Clone, Copy, Debug)]
+pub struct PixelShift {
+    amplitude: u8,
+    interval_ms: u32,
+    index: u16,
+    last_ms: Option<u32>,
+}
+
+impl PixelShift {
+    /// Create a shift moving the image every `interval_ms` milliseconds by up to `amplitude`
+    /// pixels, which should be kept to one or two to stay unnoticeable. The amplitude is limited
+    /// to 8 pixels.
+    pub fn new(amplitude: u8, interval_ms: u32) -> Self {
+        let amplitude = amplitude.min(8);
+        let side = 2 * amplitude as u16 + 1;
+        PixelShift {
+            amplitude,
+            interval_ms,
+            // Start in the middle of the square, at the unshifted position
+            index: side * side / 2,
+            last_ms: None,
+        }
+    }
+
+    /// Get the current offset of the image
+    pub fn offset(&self) -> (i8, i8) {
+        let side = 2 * self.amplitude as u16 + 1;
+        let (col, row) = (self.index % side, self.index / side);
+        // Walk every other row backwards so consecutive offsets are neighbours
+        let col = if row % 2 == 1 { side - 1 - col } else { col };
+        let a = self.amplitude as i16;
+        ((col as i16 - a) as i8, (row as i16 - a) as i8)
+    }
+
+    /// Advance to the next offset if `interval_ms` passed since the last one at the monotonic
+    /// time `now_ms`. The first call starts the interval. Returns `true` if the offset changed.
+    pub(crate) fn advance(&mut self, now_ms: u32) -> bool {
+        let last = match self.last_ms {
+            Some(last) => last,
+            None => {
+                self.last_ms = Some(now_ms);
+                return false;
+            }
+        };
+        if now_ms.wrapping_sub(last) < self.interval_ms || self.amplitude == 0 {
+            return false;
+        }
+        let side = 2 * self.amplitude as u16 + 1;
+        self.index = (self.index + 1) % (side * side);
+        self.last_ms = Some(now_ms);
+        true
+    }
+}
+
+/// What a [`Screensaver`] does to the display once it is idle
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScreensaverAction {
+    /// Turn the panel off
+    Blank,
+    /// Lower the brightness to the given level
+    Dim(u8),
+}
+
+/// Blanks or dims the display after a period without user activity and restores it on the next
+/// activity. Report activity with `touch` and apply it with
+/// [`GraphicsMode::update_screensaver`](../mode/graphics/struct.GraphicsMode.html#method.update_screensaver).
+#[derive(Clone, Copy, Debug)]
+pub struct Screensaver {
+    timeout_ms: u32,
+    action: ScreensaverAction,
+    last_activity_ms: u32,
+    woken: bool,
+    active: Option<u8>,
+}
+
+impl Screensaver {
+    /// Create a screensaver doing `action` after `timeout_ms` milliseconds without activity,
+    /// counted from the monotonic time `now_ms`
+    pub fn new(timeout_ms: u32, action: ScreensaverAction, now_ms: u32) -> Self {
+        Screensaver {
+            timeout_ms,
+            action,
+            last_activity_ms: now_ms,
+            woken: false,
+            active: None,
+        }
+    }
+
+    /// Record user activity at the monotonic time `now_ms`
+    pub fn touch(&mut self, now_ms: u32) {
+        self.last_activity_ms = now_ms;
+        self.woken = true;
+    }
+
+    /// Whether the display is currently blanked or dimmed
+    pub fn is_active(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// Get the action taken once idle
+    pub fn action(&self) -> ScreensaverAction {
+        self.action
+    }
+
+    /// The brightness to restore when waking up, or `None` if the screensaver is inactive
+    pub(crate) fn saved_brightness(&self) -> Option<u8> {
+        self.active
+    }
+
+    /// Whether activity was reported since the screensaver became active, clearing the flag
+    pub(crate) fn take_wake(&mut self) -> bool {
+        core::mem::replace(&mut self.woken, false)
+    }
+
+    /// Whether the timeout passed at `now_ms`
+    pub(crate) fn is_idle(&self, now_ms: u32) -> bool {
+        now_ms.wrapping_sub(self.last_activity_ms) >= self.timeout_ms
+    }
+
+    /// Mark the screensaver active, remembering the brightness to restore
+    pub(crate) fn set_active(&mut self, brightness: Option<u8>) {
+        self.active = brightness;
+    }
+}
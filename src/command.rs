@@ -1,20 +1,36 @@
 use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
 
+use crate::properties::{
+    ClockDivider, DataBus, GpioMode, LockState, PhaseLength, PrechargeVoltage,
+    SecondPrechargePeriod, Vsl,
+};
+
 const REMAP_BASE: u8 = 0b00100100;
 
+/// Commands ignored by the controller while protected commands are locked
+const PROTECTED_COMMANDS: [u8; 6] = [0xA2, 0xB1, 0xB3, 0xBB, 0xBE, 0xC1];
+
+/// Whether the raw command byte is ignored by the controller while protected commands are locked
+pub(crate) fn is_protected_command(command: u8) -> bool {
+    PROTECTED_COMMANDS.contains(&command)
+}
+
+#[derive(Clone, Copy)]
 pub enum Command {
     /// Column address
     Column(u8, u8),
     /// Row address
     Row(u8, u8),
     /// CommandLock
-    CommandLock(u8),
+    CommandLock(LockState),
     /// DisplayOn
     DisplayOn(bool),
     /// WriteRam
     WriteRam,
+    /// ReadRam
+    ReadRam,
     /// ClockDiv
-    ClockDiv(u8),
+    ClockDiv(ClockDivider),
     /// MuxRatio
     MuxRatio(u8),
     /// SetRemap - horizontal or vertical increment, SegmentRemap, Reverse scan direction
@@ -23,39 +39,44 @@ pub enum Command {
     StartLine(u8),
     /// DisplayOffset
     DisplayOffset(u8),
-    /// SetGpio
-    SetGpio(u8),
-    /// FunctionSelect
-    FunctionSelect(u8),
-    /// PreCharge
-    PreCharge(u8),
+    /// SetGpio - GPIO0 mode, GPIO1 mode
+    SetGpio(GpioMode, GpioMode),
+    /// FunctionSelect - data bus width, internal VDD regulator
+    FunctionSelect(DataBus, bool),
+    /// PhaseLength - reset (phase 1) and first precharge (phase 2) periods
+    PhaseLength(PhaseLength),
+    /// PrechargeVoltage
+    PrechargeVoltage(PrechargeVoltage),
     /// Vcomh
     Vcomh(u8),
     /// NormalDisplayOn
     Invert(bool),
-    /// Contrast
-    Contrast(u8),
-    /// ContrastMaster
+    /// Contrast - red, green and blue contrast current
+    Contrast(u8, u8, u8),
+    /// ContrastMaster - master contrast current from 0 (1/16) to 15 (16/16)
     ContrastCurrent(u8),
     /// SetVsl
-    SetVsl,
-    /// SetPrecharge
-    PreCharge2(u8),
-    // PhaseLength(u8)
+    SetVsl(Vsl),
+    /// SecondPrecharge - second precharge (phase 3) period
+    SecondPrecharge(SecondPrechargePeriod),
+    /// DisplayEnhancement
+    DisplayEnhancement(bool),
 }
 
 impl Command {
-    /// Send command to SSD1351
-    pub fn send<DI>(self, iface: &mut DI) -> Result<(), DisplayError>
-    where
-        DI: WriteOnlyDataCommand,
-    {
-        // Transform command into a fixed size array of 7 u8 and the real length for sending
+    /// Whether the command is ignored by the controller while protected commands are locked
+    pub fn is_protected(&self) -> bool {
+        is_protected_command(self.encode().0)
+    }
+
+    /// Transform command into its command byte, a fixed size array of argument bytes and the
+    /// real number of arguments
+    pub(crate) fn encode(self) -> (u8, [u8; 6], usize) {
         // TODO can we replace the use if the static buffers?
-        let (command, data, len) = match self {
-            Command::CommandLock(val) => (0xFD, [val, 0, 0, 0, 0, 0], 1),
+        match self {
+            Command::CommandLock(state) => (0xFD, [state.bits(), 0, 0, 0, 0, 0], 1),
             Command::DisplayOn(val) => (if val { 0xAF } else { 0xAE }, [0, 0, 0, 0, 0, 0], 0),
-            Command::ClockDiv(val) => (0xB3, [val, 0, 0, 0, 0, 0], 1),
+            Command::ClockDiv(clock) => (0xB3, [clock.bits(), 0, 0, 0, 0, 0], 1),
             Command::MuxRatio(val) => (0xCA, [val, 0, 0, 0, 0, 0], 1),
             Command::SetRemap(incr, remap, scan) => (
                 0xA0,
@@ -73,17 +94,36 @@ impl Command {
             Command::Row(start, end) => (0x75, [start, end, 0, 0, 0, 0], 2),
             Command::StartLine(val) => (0xA1, [val, 0, 0, 0, 0, 0], 1),
             Command::DisplayOffset(val) => (0xA2, [val, 0, 0, 0, 0, 0], 1),
-            Command::SetGpio(val) => (0xB5, [val, 0, 0, 0, 0, 0], 1),
-            Command::FunctionSelect(val) => (0xAB, [val, 0, 0, 0, 0, 0], 1),
-            Command::PreCharge(val) => (0xB1, [val, 0, 0, 0, 0, 0], 1),
+            Command::SetGpio(gpio0, gpio1) => {
+                (0xB5, [gpio0.bits() | gpio1.bits() << 2, 0, 0, 0, 0, 0], 1)
+            }
+            Command::FunctionSelect(bus, regulator) => {
+                (0xAB, [bus.bits() | regulator as u8, 0, 0, 0, 0, 0], 1)
+            }
+            Command::PhaseLength(phase) => (0xB1, [phase.bits(), 0, 0, 0, 0, 0], 1),
+            Command::PrechargeVoltage(voltage) => (0xBB, [voltage.level(), 0, 0, 0, 0, 0], 1),
             Command::Vcomh(val) => (0xBE, [val, 0, 0, 0, 0, 0], 1),
             Command::Invert(val) => (if val { 0xA7 } else { 0xA6 }, [0, 0, 0, 0, 0, 0], 0),
-            Command::Contrast(val) => (0xC1, [0xC8, val, 0xC8, 0, 0, 0], 3),
-            Command::ContrastCurrent(val) => (0xC7, [val, 0, 0, 0, 0, 0], 1),
-            Command::SetVsl => (0xB4, [0xA0, 0xB5, 0x55, 0, 0, 0], 3),
-            Command::PreCharge2(val) => (0xB6, [val, 0, 0, 0, 0, 0], 1),
+            Command::Contrast(red, green, blue) => (0xC1, [red, green, blue, 0, 0, 0], 3),
+            Command::ContrastCurrent(val) => (0xC7, [val & 0x0F, 0, 0, 0, 0, 0], 1),
+            Command::SetVsl(vsl) => (0xB4, [vsl.bits(), 0xB5, 0x55, 0, 0, 0], 3),
+            Command::SecondPrecharge(period) => (0xB6, [period.dclks(), 0, 0, 0, 0, 0], 1),
+            Command::DisplayEnhancement(enable) => (
+                0xB2,
+                [if enable { 0xA4 } else { 0x00 }, 0x00, 0x00, 0, 0, 0],
+                3,
+            ),
             Command::WriteRam => (0x5C, [0, 0, 0, 0, 0, 0], 0),
-        };
+            Command::ReadRam => (0x5D, [0, 0, 0, 0, 0, 0], 0),
+        }
+    }
+
+    /// Send command to SSD1351
+    pub fn send<DI>(self, iface: &mut DI) -> Result<(), DisplayError>
+    where
+        DI: WriteOnlyDataCommand,
+    {
+        let (command, data, len) = self.encode();
 
         // Send command over the interface
         iface.send_commands(DataFormat::U8(&[command]))?;
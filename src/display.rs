@@ -1,19 +1,125 @@
 //! Container to store and set display properties
 
-use crate::command::Command;
+use crate::command::{is_protected_command, Command};
+use crate::error::Error;
+use crate::interface::{NoResetPin, ReadDataCommand};
 
+use crate::properties::ClockDivider;
+use crate::properties::DataBus;
 use crate::properties::DisplayRotation;
 use crate::properties::DisplaySize;
+use crate::properties::GpioMode;
+use crate::properties::LockState;
+use crate::properties::PhaseLength;
+use crate::properties::PrechargeVoltage;
+use crate::properties::ResetTiming;
+use crate::properties::SecondPrechargePeriod;
+use crate::properties::Vsl;
 
 use display_interface::DataFormat;
 use display_interface::DisplayError;
 use display_interface::WriteOnlyDataCommand;
+use hal::delay::DelayNs;
+use hal::digital::OutputPin;
 
 /// Display properties struct
-pub struct Display<DI> {
+pub struct Display<DI, RST = NoResetPin> {
     iface: DI,
+    rst: RST,
+    reset_timing: ResetTiming,
     display_size: DisplaySize,
     display_rotation: DisplayRotation,
+    data_bus: DataBus,
+    gpio0: GpioMode,
+    gpio1: GpioMode,
+    interface_locked: bool,
+    protected_locked: bool,
+    clock_divider: ClockDivider,
+    phase_length: PhaseLength,
+    precharge_voltage: PrechargeVoltage,
+    second_precharge_period: SecondPrechargePeriod,
+    vsl: Vsl,
+    enhancement: bool,
+    brightness: u8,
+    channel_contrast: (u8, u8, u8),
+    address: AddressCache,
+}
+
+/// Last known address window, RAM pointer and increment direction of the controller, used to
+/// skip redundant addressing commands. Anything unknown is `None`.
+#[derive(Clone, Copy, Default)]
+struct AddressCache {
+    columns: Option<(u8, u8)>,
+    rows: Option<(u8, u8)>,
+    column: Option<u8>,
+    row: Option<u8>,
+    vertical_increment: Option<bool>,
+    writing: bool,
+}
+
+impl AddressCache {
+    /// Track a command sent to the controller
+    fn command(&mut self, command: &Command) {
+        self.writing = false;
+        match *command {
+            Command::Column(start, end) => {
+                self.columns = Some((start, end));
+                self.column = Some(start);
+            }
+            Command::Row(start, end) => {
+                self.rows = Some((start, end));
+                self.row = Some(start);
+            }
+            Command::WriteRam => self.writing = true,
+            Command::SetRemap(increment, _, _) => self.vertical_increment = Some(increment),
+            _ => {}
+        }
+    }
+
+    /// Advance the RAM pointer by the number of pixels written
+    fn advance(&mut self, pixels: usize) {
+        let state = (
+            self.writing,
+            self.columns,
+            self.rows,
+            self.column,
+            self.row,
+            self.vertical_increment,
+        );
+        let (columns, rows, column, row, vertical) = match state {
+            (true, Some(columns), Some(rows), Some(column), Some(row), Some(vertical)) => {
+                (columns, rows, column, row, vertical)
+            }
+            _ => {
+                self.column = None;
+                self.row = None;
+                return;
+            }
+        };
+        if column < columns.0 || column > columns.1 || row < rows.0 || row > rows.1 {
+            self.column = None;
+            self.row = None;
+            return;
+        }
+
+        // Walk the window as a wrapping sequence of pixels in increment order
+        let width = (columns.1 - columns.0) as usize + 1;
+        let height = (rows.1 - rows.0) as usize + 1;
+        let (x, y) = ((column - columns.0) as usize, (row - rows.0) as usize);
+        let index = if vertical {
+            x * height + y
+        } else {
+            y * width + x
+        };
+        let index = (index + pixels) % (width * height);
+        let (x, y) = if vertical {
+            (index / height, index % height)
+        } else {
+            (index % width, index / width)
+        };
+        self.column = Some(columns.0 + x as u8);
+        self.row = Some(rows.0 + y as u8);
+    }
 }
 
 impl<DI> Display<DI>
@@ -28,8 +134,85 @@ where
     ) -> Display<DI> {
         Display {
             iface,
+            rst: NoResetPin,
+            reset_timing: ResetTiming::default(),
             display_size,
             display_rotation,
+            data_bus: DataBus::Serial,
+            gpio0: GpioMode::HiZInputDisabled,
+            gpio1: GpioMode::HiZInputDisabled,
+            interface_locked: false,
+            protected_locked: false,
+            clock_divider: ClockDivider::new(15, 1).unwrap(),
+            phase_length: PhaseLength::new(5, 3).unwrap(),
+            precharge_voltage: PrechargeVoltage::new(0x17).unwrap(),
+            second_precharge_period: SecondPrechargePeriod::new(1).unwrap(),
+            vsl: Vsl::External,
+            enhancement: false,
+            brightness: 255,
+            channel_contrast: (0xC8, 0x8F, 0xC8),
+            address: AddressCache::default(),
+        }
+    }
+}
+
+impl<DI, RST> Display<DI, RST>
+where
+    DI: WriteOnlyDataCommand,
+{
+    /// Use the given pin to reset the display
+    pub(crate) fn with_reset_pin<R>(self, rst: R) -> Display<DI, R> {
+        Display {
+            iface: self.iface,
+            rst,
+            reset_timing: self.reset_timing,
+            display_size: self.display_size,
+            display_rotation: self.display_rotation,
+            data_bus: self.data_bus,
+            gpio0: self.gpio0,
+            gpio1: self.gpio1,
+            interface_locked: self.interface_locked,
+            protected_locked: self.protected_locked,
+            clock_divider: self.clock_divider,
+            phase_length: self.phase_length,
+            precharge_voltage: self.precharge_voltage,
+            second_precharge_period: self.second_precharge_period,
+            vsl: self.vsl,
+            enhancement: self.enhancement,
+            brightness: self.brightness,
+            channel_contrast: self.channel_contrast,
+            address: self.address,
+        }
+    }
+
+    /// Set the timings of the hardware reset sequence
+    pub(crate) fn with_reset_timing(self, reset_timing: ResetTiming) -> Self {
+        Display {
+            reset_timing,
+            ..self
+        }
+    }
+
+    /// Set the brightness and channel contrast applied by `init`
+    pub(crate) fn with_brightness(self, brightness: u8, channel_contrast: (u8, u8, u8)) -> Self {
+        Display {
+            brightness,
+            channel_contrast,
+            ..self
+        }
+    }
+
+    /// Set the data bus the display is connected to
+    pub(crate) fn with_data_bus(self, data_bus: DataBus) -> Self {
+        Display { data_bus, ..self }
+    }
+
+    /// Set the GPIO modes applied by `init`
+    pub(crate) fn with_gpio(self, gpio0: GpioMode, gpio1: GpioMode) -> Self {
+        Display {
+            gpio0,
+            gpio1,
+            ..self
         }
     }
 
@@ -38,68 +221,349 @@ where
         self.iface
     }
 
+    /// Release all resources used by the Display, including the reset pin
+    pub fn release_with_reset_pin(self) -> (DI, RST) {
+        (self.iface, self.rst)
+    }
+
+    /// Reset the display with the reset pin given to the builder, using the configured
+    /// [ResetTiming]. Without a reset pin only the delays are applied.
+    pub fn reset<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), Error>
+    where
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        let timing = self.reset_timing;
+        let pin_error = |_| Error::Interface(DisplayError::RSError);
+        self.address = AddressCache::default();
+
+        self.rst.set_high().map_err(pin_error)?;
+        delay.delay_us(timing.before_us);
+        self.rst.set_low().map_err(pin_error)?;
+        delay.delay_us(timing.low_us);
+        self.rst.set_high().map_err(pin_error)?;
+        delay.delay_us(timing.after_us);
+        Ok(())
+    }
+
+    /// Forget the tracked window and RAM pointer, e.g. when the controller was reset with a pin
+    /// the driver doesn't own
+    pub(crate) fn forget_address(&mut self) {
+        self.address = AddressCache::default();
+    }
+
+    /// Reset the display and initialise it again with the current settings, e.g. to recover
+    /// from a brown out or a corrupted controller state
+    pub fn reinit<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), Error>
+    where
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        self.reset(delay)?;
+        self.init()
+    }
+
+    /// Get the timings of the hardware reset sequence
+    pub fn get_reset_timing(&self) -> ResetTiming {
+        self.reset_timing
+    }
+
     /// Initialise the display in column mode (i.e. a byte walks down a column of 8 pixels) with
     /// column 0 on the left and column _(display_width - 1)_ on the right.
-    pub fn init(&mut self) -> Result<(), DisplayError> {
+    ///
+    /// The controller is unlocked while it is set up, any interface or protected command lock
+    /// requested before is applied again afterwards.
+    pub fn init(&mut self) -> Result<(), Error> {
         let (_, display_height) = self.display_size.dimensions();
+        let (interface_locked, protected_locked) = (self.interface_locked, self.protected_locked);
+        // The controller may have been reset since the addressing was last tracked
+        self.address = AddressCache::default();
 
         // TODO: Break up into nice bits so display modes can pick whathever they need
-        Command::CommandLock(0x12).send(&mut self.iface)?;
-        Command::CommandLock(0xB1).send(&mut self.iface)?;
-        Command::DisplayOn(false).send(&mut self.iface)?;
-        Command::ClockDiv(0xF1).send(&mut self.iface)?;
-        Command::MuxRatio(display_height - 1).send(&mut self.iface)?;
-        Command::DisplayOffset(0).send(&mut self.iface)?;
-        Command::StartLine(0).send(&mut self.iface)?;
-        Command::SetGpio(0x00).send(&mut self.iface)?;
-        Command::FunctionSelect(0x01).send(&mut self.iface)?;
-        Command::SetVsl.send(&mut self.iface)?;
-        Command::Contrast(0x8F).send(&mut self.iface)?;
-        Command::ContrastCurrent(0x0F).send(&mut self.iface)?;
-        // Command::PhaseLength(0x32).send(&mut self.iface)?;
-        // Command::PreCharge(0x17).send(&mut self.iface)?;
-        Command::PreCharge(0x32).send(&mut self.iface)?;
-        Command::PreCharge2(0x01).send(&mut self.iface)?;
-        Command::Vcomh(0x05).send(&mut self.iface)?;
-        Command::Invert(false).send(&mut self.iface)?;
-
-        self.set_rotation(self.display_rotation).unwrap();
+        self.unlock_interface()?;
+        self.unlock_protected_commands()?;
+        self.send(Command::DisplayOn(false))?;
+        self.send(Command::ClockDiv(self.clock_divider))?;
+        self.send(Command::MuxRatio(display_height - 1))?;
+        self.send(Command::DisplayOffset(0))?;
+        self.send(Command::StartLine(0))?;
+        self.send(Command::SetGpio(self.gpio0, self.gpio1))?;
+        self.send(Command::FunctionSelect(self.data_bus, true))?;
+        self.send(Command::SetVsl(self.vsl))?;
+        self.send_brightness(self.brightness, self.channel_contrast)?;
+        self.send(Command::PhaseLength(self.phase_length))?;
+        self.send(Command::PrechargeVoltage(self.precharge_voltage))?;
+        self.send(Command::SecondPrecharge(self.second_precharge_period))?;
+        self.send(Command::DisplayEnhancement(self.enhancement))?;
+        self.send(Command::Vcomh(0x05))?;
+        self.send(Command::Invert(false))?;
+
+        self.set_rotation(self.display_rotation)?;
 
         self.clear()?;
 
-        Command::DisplayOn(true).send(&mut self.iface)?;
+        self.send(Command::DisplayOn(true))?;
+
+        if protected_locked {
+            self.lock_protected_commands()?;
+        }
+        if interface_locked {
+            self.lock_interface()?;
+        }
+
+        Ok(())
+    }
+
+    /// Send a command to the display, refusing it if the controller would ignore it because of
+    /// the current command lock state
+    fn send(&mut self, command: Command) -> Result<(), Error> {
+        if self.interface_locked
+            && !matches!(command, Command::CommandLock(LockState::InterfaceUnlocked))
+        {
+            return Err(Error::InterfaceLocked);
+        }
+        if self.protected_locked && command.is_protected() {
+            return Err(Error::CommandLocked);
+        }
+        if let Err(error) = command.send(&mut self.iface) {
+            self.address = AddressCache::default();
+            return Err(error.into());
+        }
+        self.address.command(&command);
+        Ok(())
+    }
+
+    /// Send a command to the display, unlocking protected commands around it if needed
+    pub(crate) fn send_unlocked(&mut self, command: Command) -> Result<(), Error> {
+        let (command, data, len) = command.encode();
+        self.send_raw(command, &data[0..len])
+    }
+
+    /// Send a raw command byte and its arguments to the display, unlocking protected commands
+    /// around it if needed. Command lock (0xFD) commands update the tracked lock state.
+    pub(crate) fn send_raw(&mut self, command: u8, data: &[u8]) -> Result<(), Error> {
+        if command == 0xFD {
+            return match data {
+                [0x12] => self.unlock_interface(),
+                [0x16] => self.lock_interface(),
+                [0xB0] => self.lock_protected_commands(),
+                [0xB1] => self.unlock_protected_commands(),
+                _ => Err(Error::OutOfRange),
+            };
+        }
+        if self.interface_locked {
+            return Err(Error::InterfaceLocked);
+        }
+        // Raw commands may change the addressing in ways which aren't tracked
+        self.address = AddressCache::default();
 
+        let relock = self.protected_locked && is_protected_command(command);
+        if relock {
+            Command::CommandLock(LockState::ProtectedUnlocked).send(&mut self.iface)?;
+        }
+        let result = self
+            .iface
+            .send_commands(DataFormat::U8(&[command]))
+            .and_then(|_| {
+                if data.is_empty() {
+                    Ok(())
+                } else {
+                    self.iface.send_data(DataFormat::U8(data))
+                }
+            });
+        if relock {
+            Command::CommandLock(LockState::ProtectedLocked).send(&mut self.iface)?;
+        }
+        result?;
         Ok(())
     }
 
     /// Clear the display by setting all pixels to black
-    pub fn clear(&mut self) -> Result<(), DisplayError> {
+    pub fn clear(&mut self) -> Result<(), Error> {
         let (display_width, display_height) = self.display_size.dimensions();
         self.set_draw_area((0, 0), (display_width, display_height))?;
-        for _ in 0..(display_height as u32 * display_width as u32) {
-            self.iface.send_data(DataFormat::U8(&[0x00, 0x00]))?; // send 8 * 2 bits
-        }
-        Ok(())
+        self.draw_repeated(0, self.display_size.num_pixels())
     }
 
     /// Set the position in the framebuffer of the display where any sent data should be
     /// drawn. This method can be used for changing the affected area on the screen as well
     /// as (re-)setting the start point of the next `draw` call.
-    pub fn set_draw_area(&mut self, start: (u8, u8), end: (u8, u8)) -> Result<(), DisplayError> {
-        Command::Column(start.0, end.0 - 1).send(&mut self.iface)?;
-        Command::Row(start.1, end.1 - 1).send(&mut self.iface)?;
-        Command::WriteRam.send(&mut self.iface)?;
+    ///
+    /// Commands are only sent as needed: when the window is unchanged and the previous write
+    /// ended at `start`, nothing is sent at all.
+    pub fn set_draw_area(&mut self, start: (u8, u8), end: (u8, u8)) -> Result<(), Error> {
+        let columns = (start.0, end.0 - 1);
+        let rows = (start.1, end.1 - 1);
+        let address = self.address;
+        let send_column = address.columns != Some(columns) || address.column != Some(start.0);
+        let send_row = address.rows != Some(rows) || address.row != Some(start.1);
+
+        if send_column {
+            self.send(Command::Column(columns.0, columns.1))?;
+        }
+        if send_row {
+            self.send(Command::Row(rows.0, rows.1))?;
+        }
+        if send_column || send_row || !address.writing {
+            self.send(Command::WriteRam)?;
+        }
         Ok(())
     }
 
+    #[cfg(not(feature = "buffered"))]
+    /// Get the window the controller is known to be writing into, as its start and end like
+    /// `set_draw_area` takes them, and the position the next pixel goes to
+    pub(crate) fn draw_area(&self) -> Option<[(u8, u8); 3]> {
+        let address = self.address;
+        if !address.writing {
+            return None;
+        }
+        let (columns, rows) = (address.columns?, address.rows?);
+        Some([
+            (columns.0, rows.0),
+            (columns.1 + 1, rows.1 + 1),
+            (address.column?, address.row?),
+        ])
+    }
+
     /// Send the data to the display for drawing at the current position in the framebuffer
     /// and advance the position accordingly. Cf. `set_draw_area` to modify the affected area by
     /// this method.
-    pub fn draw(&mut self, buffer: &[u8]) -> Result<(), DisplayError> {
-        self.iface.send_data(DataFormat::U8(buffer))?;
+    ///
+    /// On a 16 bit parallel bus every pixel is a single bus cycle, so a buffer of an odd length
+    /// returns [`Error::OutOfRange`] without sending anything.
+    pub fn draw(&mut self, buffer: &[u8]) -> Result<(), Error> {
+        if self.interface_locked {
+            return Err(Error::InterfaceLocked);
+        }
+        if self.data_bus == DataBus::Parallel16Bit && buffer.len() & 1 != 0 {
+            return Err(Error::OutOfRange);
+        }
+        if buffer.len() & 1 == 0 {
+            self.address.advance(buffer.len() / 2);
+        } else {
+            // A split pixel leaves the pointer in between two pixels
+            self.address.column = None;
+            self.address.row = None;
+        }
+        let result = self.draw_bytes(buffer);
+        if result.is_err() {
+            self.address = AddressCache::default();
+        }
+        result
+    }
+
+    /// Send the bytes of `draw` in the format of the data bus
+    fn draw_bytes(&mut self, buffer: &[u8]) -> Result<(), Error> {
+        match self.data_bus {
+            DataBus::Serial | DataBus::Parallel8Bit => {
+                self.iface.send_data(DataFormat::U8(buffer))?;
+            }
+            DataBus::Parallel16Bit => {
+                // Every pixel is a single bus cycle, pack the bytes into words. `draw` only lets
+                // whole pixels through.
+                let mut words = [0u16; 32];
+                for chunk in buffer.chunks(words.len() * 2) {
+                    let len = chunk.len() / 2;
+                    for (word, bytes) in words.iter_mut().zip(chunk.chunks_exact(2)) {
+                        *word = u16::from_be_bytes([bytes[0], bytes[1]]);
+                    }
+                    self.iface.send_data(DataFormat::U16(&words[..len]))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "buffered")]
+    /// Send a whole frame of big endian Rgb565 pixels, in rotated coordinates, shifted by
+    /// `offset` columns and rows. The edges exposed by the shift are padded with black.
+    pub(crate) fn draw_frame(&mut self, frame: &[u8], offset: (i8, i8)) -> Result<(), Error> {
+        let (display_width, display_height) = self.display_size.dimensions();
+        self.set_draw_area((0, 0), (display_width, display_height))?;
+        if offset == (0, 0) {
+            return self.draw(frame);
+        }
+
+        // Stream the rows shifted into the window, padding the exposed edges with black
+        const BLACK: [u8; 256] = [0; 256];
+        let (width, height) = self.get_dimensions();
+        let stride = width as usize * 2;
+        let (ox, oy) = offset;
+        let shift = (ox.unsigned_abs() as usize * 2).min(stride);
+        for row in 0..height as i32 {
+            let src = row - oy as i32;
+            if src < 0 || src >= height as i32 {
+                self.draw(&BLACK[..stride])?;
+                continue;
+            }
+            let line = &frame[src as usize * stride..(src as usize + 1) * stride];
+            if ox > 0 {
+                self.draw(&BLACK[..shift])?;
+                self.draw(&line[..stride - shift])?;
+            } else if ox < 0 {
+                self.draw(&line[shift..])?;
+                self.draw(&BLACK[..shift])?;
+            } else {
+                self.draw(line)?;
+            }
+        }
         Ok(())
     }
 
+    /// Send Rgb565 pixels to the display for drawing at the current position in the framebuffer
+    /// and advance the position accordingly, like `draw`.
+    pub fn draw_pixels(&mut self, pixels: &[u16]) -> Result<(), Error> {
+        if self.interface_locked {
+            return Err(Error::InterfaceLocked);
+        }
+        self.address.advance(pixels.len());
+        let result = match self.data_bus {
+            DataBus::Serial | DataBus::Parallel8Bit => self
+                .iface
+                .send_data(DataFormat::U16BEIter(&mut pixels.iter().copied())),
+            DataBus::Parallel16Bit => self.iface.send_data(DataFormat::U16(pixels)),
+        };
+        if result.is_err() {
+            self.address = AddressCache::default();
+        }
+        Ok(result?)
+    }
+
+    /// Send `count` pixels of one Rgb565 colour to the display, like `draw_pixels`. On a serial or
+    /// 8 bit bus this is a single transfer.
+    pub fn draw_repeated(&mut self, color: u16, count: usize) -> Result<(), Error> {
+        if self.interface_locked {
+            return Err(Error::InterfaceLocked);
+        }
+        self.address.advance(count);
+        let result = match self.data_bus {
+            DataBus::Serial | DataBus::Parallel8Bit => self
+                .iface
+                .send_data(DataFormat::U16BEIter(&mut (0..count).map(|_| color))),
+            DataBus::Parallel16Bit => {
+                let words = [color; 32];
+                let mut remaining = count;
+                let mut result = Ok(());
+                while remaining > 0 && result.is_ok() {
+                    let len = remaining.min(words.len());
+                    result = self.iface.send_data(DataFormat::U16(&words[..len]));
+                    remaining -= len;
+                }
+                result
+            }
+        };
+        if result.is_err() {
+            self.address = AddressCache::default();
+        }
+        Ok(result?)
+    }
+
+    /// Get the data bus the display is connected to
+    pub fn get_data_bus(&self) -> DataBus {
+        self.data_bus
+    }
+
     /// Get the configured display size
     pub fn get_size(&self) -> DisplaySize {
         self.display_size
@@ -108,11 +572,15 @@ where
     /// Get display dimensions, taking into account the current rotation of the display
     ///
     /// ```rust
+    /// # use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+    /// # use ssd1351::display::Display;
+    /// # use ssd1351::properties::{DisplayRotation, DisplaySize};
+    /// #
     /// # struct FakeInterface;
     /// #
-    /// # impl DisplayInterface for FakeInterface {
-    /// #     fn send_command(&mut self, cmd: u8) -> Result<(), ()> { Ok(()) }
-    /// #     fn send_data(&mut self, buf: &[u8]) -> Result<(), ()> { Ok(()) }
+    /// # impl WriteOnlyDataCommand for FakeInterface {
+    /// #     fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> { Ok(()) }
+    /// #     fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> { Ok(()) }
     /// # }
     /// #
     /// # let interface = FakeInterface {};
@@ -147,24 +615,646 @@ where
     }
 
     /// Set the display rotation
-    pub fn set_rotation(&mut self, display_rotation: DisplayRotation) -> Result<(), DisplayError> {
+    pub fn set_rotation(&mut self, display_rotation: DisplayRotation) -> Result<(), Error> {
         self.display_rotation = display_rotation;
+        self.set_column_major(false)
+    }
 
-        match display_rotation {
-            DisplayRotation::Rotate0 => {
-                Command::SetRemap(false, false, true).send(&mut self.iface)?;
-            }
-            DisplayRotation::Rotate90 => {
-                Command::SetRemap(true, true, true).send(&mut self.iface)?;
-            }
-            DisplayRotation::Rotate180 => {
-                Command::SetRemap(false, true, false).send(&mut self.iface)?;
+    /// Set the address increment so that consecutive pixels are drawn down the columns of the
+    /// rotated display instead of along its rows. Changing the rotation switches back to row
+    /// major order.
+    pub fn set_column_major(&mut self, column_major: bool) -> Result<(), Error> {
+        let (increment, remap, scan) = match self.display_rotation {
+            DisplayRotation::Rotate0 => (false, false, true),
+            DisplayRotation::Rotate90 => (true, true, true),
+            DisplayRotation::Rotate180 => (false, true, false),
+            DisplayRotation::Rotate270 => (true, false, false),
+        };
+        self.send(Command::SetRemap(increment != column_major, remap, scan))
+    }
+
+    /// Get the mode of the GPIO0 pin
+    pub fn get_gpio0(&self) -> GpioMode {
+        self.gpio0
+    }
+
+    /// Get the mode of the GPIO1 pin
+    pub fn get_gpio1(&self) -> GpioMode {
+        self.gpio1
+    }
+
+    /// Set the mode of the GPIO0 pin
+    pub fn set_gpio0(&mut self, mode: GpioMode) -> Result<(), Error> {
+        self.send(Command::SetGpio(mode, self.gpio1))?;
+        self.gpio0 = mode;
+        Ok(())
+    }
+
+    /// Set the mode of the GPIO1 pin
+    pub fn set_gpio1(&mut self, mode: GpioMode) -> Result<(), Error> {
+        self.send(Command::SetGpio(self.gpio0, mode))?;
+        self.gpio1 = mode;
+        Ok(())
+    }
+
+    /// Whether the MCU interface of the controller is locked
+    pub fn is_interface_locked(&self) -> bool {
+        self.interface_locked
+    }
+
+    /// Whether the protected commands are locked
+    pub fn are_protected_commands_locked(&self) -> bool {
+        self.protected_locked
+    }
+
+    /// Lock the MCU interface of the controller. Until [`unlock_interface`](Self::unlock_interface)
+    /// is called the controller ignores all commands and memory access, and the driver refuses
+    /// them with [`Error::InterfaceLocked`].
+    pub fn lock_interface(&mut self) -> Result<(), Error> {
+        self.send(Command::CommandLock(LockState::InterfaceLocked))?;
+        self.interface_locked = true;
+        Ok(())
+    }
+
+    /// Unlock the MCU interface of the controller
+    pub fn unlock_interface(&mut self) -> Result<(), Error> {
+        self.send(Command::CommandLock(LockState::InterfaceUnlocked))?;
+        self.interface_locked = false;
+        Ok(())
+    }
+
+    /// Lock the protected commands (display offset, phase length, clock divider, precharge
+    /// voltage, VCOMH and contrast). While locked the driver refuses them with
+    /// [`Error::CommandLocked`].
+    pub fn lock_protected_commands(&mut self) -> Result<(), Error> {
+        self.send(Command::CommandLock(LockState::ProtectedLocked))?;
+        self.protected_locked = true;
+        Ok(())
+    }
+
+    /// Unlock the protected commands
+    pub fn unlock_protected_commands(&mut self) -> Result<(), Error> {
+        self.send(Command::CommandLock(LockState::ProtectedUnlocked))?;
+        self.protected_locked = false;
+        Ok(())
+    }
+
+    /// Get the reset and first precharge phase lengths
+    pub fn get_phase_length(&self) -> PhaseLength {
+        self.phase_length
+    }
+
+    /// Set the reset (phase 1) and first precharge (phase 2) periods. Panels with a larger pixel
+    /// capacitance may need longer periods to avoid ghosting.
+    pub fn set_phase_length(&mut self, phase_length: PhaseLength) -> Result<(), Error> {
+        self.send(Command::PhaseLength(phase_length))?;
+        self.phase_length = phase_length;
+        Ok(())
+    }
+
+    /// Get the display clock setting
+    pub fn get_clock_divider(&self) -> ClockDivider {
+        self.clock_divider
+    }
+
+    /// Get the nominal frame rate in Hz resulting from the clock divider, the phase lengths and
+    /// the mux ratio. The display clocks per row are the phase lengths plus the 134 DCLKs of
+    /// current drive the datasheet gives in section 8.5, the oscillator frequencies are the
+    /// estimates of [`ClockDivider::NOMINAL_FOSC_HZ`] as the datasheet leaves them TBD.
+    pub fn frame_rate(&self) -> u32 {
+        let millihertz = self.frame_rate_millihertz(self.clock_divider, self.phase_length);
+        ((millihertz + 500) / 1000) as u32
+    }
+
+    /// Pick the clock divider and phase lengths whose nominal frame rate is nearest to `hz`,
+    /// changing the phase lengths as little as possible. This replaces phase lengths set with
+    /// `set_phase_length`, which are only kept when no other setting is nearer. The result is
+    /// reported by `frame_rate`. As the oscillator frequency is only nominal, locking the
+    /// refresh to a multiple of the mains or camera frequency may need fine tuning with
+    /// `set_phase_length`.
+    pub fn set_frame_rate(&mut self, hz: u32) -> Result<(), Error> {
+        let target = hz as u64 * 1000;
+        let current = self.phase_length;
+        let mut best = (self.clock_divider, current);
+        let mut best_cost = (u64::MAX, u32::MAX);
+
+        for frequency in (0..16).rev() {
+            for divide in 0..=10 {
+                let clock = ClockDivider::new(frequency, divide)?;
+                for phase1 in (5..=31).step_by(2) {
+                    for phase2 in 3..=15 {
+                        let phase_length = PhaseLength::new(phase1, phase2)?;
+                        // Rates within 0.1 Hz count as equal, leaving the phases alone
+                        let error = self
+                            .frame_rate_millihertz(clock, phase_length)
+                            .abs_diff(target)
+                            / 100;
+                        let change = phase1.abs_diff(current.phase1()) as u32
+                            + phase2.abs_diff(current.phase2()) as u32;
+                        if (error, change) < best_cost {
+                            best = (clock, phase_length);
+                            best_cost = (error, change);
+                        }
+                    }
+                }
             }
-            DisplayRotation::Rotate270 => {
-                Command::SetRemap(true, false, false).send(&mut self.iface)?;
+        }
+
+        let (clock, phase_length) = best;
+        self.send(Command::ClockDiv(clock))?;
+        self.send(Command::PhaseLength(phase_length))?;
+        self.clock_divider = clock;
+        self.phase_length = phase_length;
+        Ok(())
+    }
+
+    /// Nominal frame rate in mHz: the display clock divided by the DCLKs per row, phase 1 and
+    /// phase 2 plus the current drive period, and the number of rows
+    fn frame_rate_millihertz(&self, clock: ClockDivider, phase_length: PhaseLength) -> u64 {
+        // The default X of the frame frequency formula in section 8.5 of the datasheet
+        const CURRENT_DRIVE_DCLKS: u64 = 134;
+
+        let (_, mux) = self.display_size.dimensions();
+        let dclks_per_row =
+            phase_length.phase1() as u64 + phase_length.phase2() as u64 + CURRENT_DRIVE_DCLKS;
+        let fosc = ClockDivider::NOMINAL_FOSC_HZ[clock.frequency() as usize] as u64;
+        fosc * 1000 / (clock.divisor() as u64 * dclks_per_row * mux as u64)
+    }
+
+    /// Get the first precharge voltage
+    pub fn get_precharge_voltage(&self) -> PrechargeVoltage {
+        self.precharge_voltage
+    }
+
+    /// Set the first precharge voltage
+    pub fn set_precharge_voltage(&mut self, voltage: PrechargeVoltage) -> Result<(), Error> {
+        self.send(Command::PrechargeVoltage(voltage))?;
+        self.precharge_voltage = voltage;
+        Ok(())
+    }
+
+    /// Get the second precharge period
+    pub fn get_second_precharge_period(&self) -> SecondPrechargePeriod {
+        self.second_precharge_period
+    }
+
+    /// Set the second precharge (phase 3) period
+    pub fn set_second_precharge_period(
+        &mut self,
+        period: SecondPrechargePeriod,
+    ) -> Result<(), Error> {
+        self.send(Command::SecondPrecharge(period))?;
+        self.second_precharge_period = period;
+        Ok(())
+    }
+
+    /// Get the segment low voltage source
+    pub fn get_vsl(&self) -> Vsl {
+        self.vsl
+    }
+
+    /// Set the segment low voltage source
+    pub fn set_vsl(&mut self, vsl: Vsl) -> Result<(), Error> {
+        self.send(Command::SetVsl(vsl))?;
+        self.vsl = vsl;
+        Ok(())
+    }
+
+    /// Whether the display enhancement is enabled
+    pub fn is_display_enhanced(&self) -> bool {
+        self.enhancement
+    }
+
+    /// Enable or disable the display enhancement
+    pub fn set_display_enhancement(&mut self, enable: bool) -> Result<(), Error> {
+        self.send(Command::DisplayEnhancement(enable))?;
+        self.enhancement = enable;
+        Ok(())
+    }
+
+    /// Turn the display panel on or off. The GDDRAM contents and settings are kept while the
+    /// display is off.
+    pub fn set_display_on(&mut self, on: bool) -> Result<(), Error> {
+        self.send(Command::DisplayOn(on))
+    }
+
+    /// Set the GDDRAM row shown on the first line of the panel, scrolling the image vertically
+    /// with the rows shifted out reappearing on the other side
+    pub fn set_start_line(&mut self, line: u8) -> Result<(), Error> {
+        self.send(Command::StartLine(line & 0x7F))
+    }
+
+    /// Get the brightness
+    pub fn get_brightness(&self) -> u8 {
+        self.brightness
+    }
+
+    /// Set the brightness from 0 (dimmest) to 255 (brightest). The upper 4 bits select the master
+    /// contrast current, the lower ones scale the channel contrast values between two master
+    /// current steps, so the white point set with `set_channel_contrast` is kept at every
+    /// brightness. Even at 0 the display is still lit, turn it off to blank it.
+    pub fn set_brightness(&mut self, brightness: u8) -> Result<(), Error> {
+        self.send_brightness(brightness, self.channel_contrast)?;
+        self.brightness = brightness;
+        Ok(())
+    }
+
+    /// Get the red, green and blue contrast values
+    pub fn get_channel_contrast(&self) -> (u8, u8, u8) {
+        self.channel_contrast
+    }
+
+    /// Set the contrast of the red, green and blue channels at full brightness, e.g. to calibrate
+    /// the white point of a panel
+    pub fn set_channel_contrast(&mut self, red: u8, green: u8, blue: u8) -> Result<(), Error> {
+        self.send_brightness(self.brightness, (red, green, blue))?;
+        self.channel_contrast = (red, green, blue);
+        Ok(())
+    }
+
+    /// Send the master current and scaled channel contrast values for a brightness
+    fn send_brightness(&mut self, brightness: u8, contrast: (u8, u8, u8)) -> Result<(), Error> {
+        let master = brightness >> 4;
+        // Fraction of the contrast kept at this master current step, 1/16 up to 16/16
+        let scale = |c: u8| (c as u32 * (brightness as u32 + 1) / (16 * (master as u32 + 1))) as u8;
+        self.send(Command::Contrast(
+            scale(contrast.0),
+            scale(contrast.1),
+            scale(contrast.2),
+        ))?;
+        self.send(Command::ContrastCurrent(master))?;
+        Ok(())
+    }
+}
+
+impl<DI, RST> Display<DI, RST>
+where
+    DI: ReadDataCommand,
+{
+    /// Read the pixels of an area of the display RAM, from `start` (inclusive) to `end`
+    /// (exclusive) in the same coordinates as `set_draw_area`, into `buf` as Rgb565 values. The
+    /// pixels are read in the same order as they are written by `draw`. `buf` must hold at least
+    /// as many pixels as the area contains.
+    pub fn read_area(
+        &mut self,
+        start: (u8, u8),
+        end: (u8, u8),
+        buf: &mut [u16],
+    ) -> Result<(), Error> {
+        let (display_width, display_height) = self.display_size.dimensions();
+        if start.0 >= end.0 || start.1 >= end.1 || end.0 > display_width || end.1 > display_height {
+            return Err(Error::OutOfRange);
+        }
+        let len = (end.0 - start.0) as usize * (end.1 - start.1) as usize;
+        let buf = buf.get_mut(..len).ok_or(Error::OutOfRange)?;
+
+        self.send(Command::Column(start.0, end.0 - 1))?;
+        self.send(Command::Row(start.1, end.1 - 1))?;
+        self.send(Command::ReadRam)?;
+
+        // Reading moves the RAM pointer, it has to be set again for the next write
+        self.address = AddressCache::default();
+
+        // The controller returns a dummy word before the first real one
+        self.iface.read_data(&mut [0])?;
+
+        match self.data_bus {
+            DataBus::Parallel16Bit => self.iface.read_data(buf)?,
+            DataBus::Serial | DataBus::Parallel8Bit => {
+                let mut bytes = [0u16; 64];
+                for pixels in buf.chunks_mut(bytes.len() / 2) {
+                    let bytes = &mut bytes[..pixels.len() * 2];
+                    self.iface.read_data(bytes)?;
+                    for (pixel, bytes) in pixels.iter_mut().zip(bytes.chunks_exact(2)) {
+                        *pixel = (bytes[0] & 0xFF) << 8 | (bytes[1] & 0xFF);
+                    }
+                }
             }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::mock::Mock;
+    use crate::mode::raw::RawMode;
+    use std::vec::Vec;
+
+    const COLUMN: u8 = 0x15;
+    const ROW: u8 = 0x75;
+    const WRITE_RAM: u8 = 0x5C;
+
+    fn display() -> Display<Mock> {
+        let mut display = Display::new(
+            Mock::default(),
+            DisplaySize::Display128x128,
+            DisplayRotation::Rotate0,
+        );
+        display.init().unwrap();
+        display.iface.commands.clear();
+        display
+    }
+
+    /// Take the codes of the commands sent since the last call
+    fn sent(display: &mut Display<Mock>) -> Vec<u8> {
+        let codes = display.iface.codes();
+        display.iface.commands.clear();
+        codes
+    }
+
+    fn cache(columns: (u8, u8), rows: (u8, u8), vertical: bool) -> AddressCache {
+        AddressCache {
+            columns: Some(columns),
+            rows: Some(rows),
+            column: Some(columns.0),
+            row: Some(rows.0),
+            vertical_increment: Some(vertical),
+            writing: true,
+        }
+    }
+
+    #[test]
+    fn advance_wraps_at_window_end() {
+        let mut address = cache((10, 13), (20, 21), false);
+        address.advance(5);
+        assert_eq!((address.column, address.row), (Some(11), Some(21)));
+        address.advance(3);
+        assert_eq!((address.column, address.row), (Some(10), Some(20)));
+        address.advance(8 * 3 + 2);
+        assert_eq!((address.column, address.row), (Some(12), Some(20)));
+
+        // Outside of a write the position is unknown
+        let mut address = cache((10, 13), (20, 21), false);
+        address.writing = false;
+        address.advance(1);
+        assert_eq!((address.column, address.row), (None, None));
+    }
+
+    #[test]
+    fn advance_vertical_increment() {
+        let mut address = cache((0, 3), (0, 1), true);
+        address.advance(3);
+        assert_eq!((address.column, address.row), (Some(1), Some(1)));
+        address.advance(4);
+        assert_eq!((address.column, address.row), (Some(3), Some(1)));
+        address.advance(1);
+        assert_eq!((address.column, address.row), (Some(0), Some(0)));
+    }
+
+    #[test]
+    fn skips_window_after_wraparound() {
+        let mut display = display();
+        display.set_draw_area((10, 20), (14, 22)).unwrap();
+        assert_eq!(sent(&mut display), [COLUMN, ROW, WRITE_RAM]);
+        display.draw_pixels(&[1; 8]).unwrap();
+
+        // The pointer wrapped to the start of the unchanged window
+        display.set_draw_area((10, 20), (14, 22)).unwrap();
+        assert!(sent(&mut display).is_empty());
+        display.draw_pixels(&[2, 3]).unwrap();
+        assert_eq!(display.iface.get(10, 20), 2);
+        assert_eq!(display.iface.get(11, 20), 3);
+
+        // Mid window the pointer is set again, here only its column is off
+        display.set_draw_area((10, 20), (14, 22)).unwrap();
+        assert_eq!(sent(&mut display), [COLUMN, WRITE_RAM]);
+
+        // Another window is always set
+        display.draw_pixels(&[0; 8]).unwrap();
+        display.set_draw_area((10, 20), (14, 23)).unwrap();
+        assert_eq!(sent(&mut display), [ROW, WRITE_RAM]);
+    }
+
+    #[test]
+    fn skips_window_with_vertical_increment() {
+        let mut display = display();
+        display.set_column_major(true).unwrap();
+        display.set_draw_area((0, 0), (4, 2)).unwrap();
+        sent(&mut display);
+        display.draw_pixels(&[1, 2, 3]).unwrap();
+        assert_eq!(display.iface.get(0, 1), 2);
+        assert_eq!(display.iface.get(1, 0), 3);
+        display.set_draw_area((0, 0), (4, 2)).unwrap();
+        assert_eq!(sent(&mut display), [COLUMN, ROW, WRITE_RAM]);
+
+        display.draw_pixels(&[4; 8]).unwrap();
+        display.set_draw_area((0, 0), (4, 2)).unwrap();
+        assert!(sent(&mut display).is_empty());
+
+        // The increment changes with another command, which ends the write
+        display.set_column_major(false).unwrap();
+        sent(&mut display);
+        display.set_draw_area((0, 0), (4, 2)).unwrap();
+        assert_eq!(sent(&mut display), [WRITE_RAM]);
+    }
+
+    #[test]
+    fn odd_length_draws() {
+        let mut display = display();
+        // A one column window, the split second pixel moves the pointer to the next row
+        display.set_draw_area((5, 0), (6, 4)).unwrap();
+        display.draw(&[0x12, 0x34, 0x56]).unwrap();
+        assert_eq!(display.iface.get(5, 0), 0x1234);
+        sent(&mut display);
+
+        display.set_draw_area((5, 0), (6, 4)).unwrap();
+        assert_eq!(sent(&mut display), [COLUMN, ROW, WRITE_RAM]);
+        display.draw(&[0xAB, 0xCD]).unwrap();
+        assert_eq!(display.iface.get(5, 0), 0xABCD);
+        assert_eq!(display.iface.get(5, 1), 0);
+    }
+
+    #[test]
+    fn odd_length_on_16_bit_bus() {
+        let mut display = display().with_data_bus(DataBus::Parallel16Bit);
+        display.set_draw_area((0, 0), (4, 1)).unwrap();
+        sent(&mut display);
+        let pixels = display.iface.pixels;
+        assert!(matches!(
+            display.draw(&[0x12, 0x34, 0x56]),
+            Err(Error::OutOfRange)
+        ));
+        assert_eq!(display.iface.pixels, pixels);
+
+        // Nothing was sent, so the position is still known
+        display.draw(&[0x12, 0x34, 0x56, 0x78]).unwrap();
+        assert_eq!(display.iface.get(0, 0), 0x1234);
+        assert_eq!(display.iface.get(1, 0), 0x5678);
+        display.draw(&[0; 4]).unwrap();
+        display.set_draw_area((0, 0), (4, 1)).unwrap();
+        assert!(sent(&mut display).is_empty());
+    }
+
+    #[test]
+    fn invalidated_after_interface_error() {
+        let mut display = display();
+        display.set_draw_area((0, 0), (2, 2)).unwrap();
+        display.iface.fail_after = Some(0);
+        assert!(display.draw_pixels(&[1; 4]).is_err());
+        sent(&mut display);
+        display.set_draw_area((0, 0), (2, 2)).unwrap();
+        assert_eq!(sent(&mut display), [COLUMN, ROW, WRITE_RAM]);
+
+        // A failed command leaves the controller state unknown as well
+        display.draw_pixels(&[1; 4]).unwrap();
+        display.iface.fail_after = Some(0);
+        assert!(display.set_draw_area((0, 0), (2, 3)).is_err());
+        display.set_draw_area((0, 0), (2, 2)).unwrap();
+        assert_eq!(sent(&mut display), [COLUMN, ROW, WRITE_RAM]);
+    }
+
+    #[test]
+    fn invalidated_after_raw_commands() {
+        let mut raw = RawMode {
+            display: display(),
+            #[cfg(feature = "buffered")]
+            buffer: &mut [],
         };
+        raw.set_window((0, 0), (2, 2)).unwrap();
+        raw.write_pixels(&[1; 4]).unwrap();
+        sent(&mut raw.display);
 
-        Ok(())
+        // The window is moved behind the cache's back
+        raw.send_command(COLUMN, &[8, 9]).unwrap();
+        raw.set_window((0, 0), (2, 2)).unwrap();
+        assert_eq!(sent(&mut raw.display), [COLUMN, COLUMN, ROW, WRITE_RAM]);
+        raw.write_pixels(&[2; 4]).unwrap();
+        assert_eq!(raw.display.iface.get(8, 0), 0);
+
+        raw.send(Command::Row(5, 6)).unwrap();
+        raw.set_window((0, 0), (2, 2)).unwrap();
+        assert_eq!(sent(&mut raw.display), [ROW, COLUMN, ROW, WRITE_RAM]);
+    }
+
+    #[test]
+    fn invalidated_by_init() {
+        let mut display = Display::new(
+            Mock::default(),
+            DisplaySize::Display128x96,
+            DisplayRotation::Rotate0,
+        );
+        display.init().unwrap();
+
+        // The controller comes out of a reset with the full 128x128 window
+        display.iface = Mock::default();
+        display.init().unwrap();
+        display.set_draw_area((0, 0), (128, 96)).unwrap();
+        display.draw_pixels(&[1]).unwrap();
+        assert_eq!(display.iface.get(0, 0), 1);
+        assert_eq!(display.iface.get(0, 96), 0);
+    }
+
+    #[test]
+    fn frame_rate_settings() {
+        const CLOCK_DIV: u8 = 0xB3;
+        const PHASE_LENGTH: u8 = 0xB1;
+
+        // Size, rate, clock divider and phase length bytes
+        let cases = [
+            (DisplaySize::Display128x128, 50, 0x21, 0x32),
+            (DisplaySize::Display128x128, 60, 0x61, 0x82),
+            (DisplaySize::Display128x96, 50, 0xC2, 0xD2),
+            (DisplaySize::Display128x96, 60, 0x21, 0xF4),
+        ];
+        for (size, hz, clock, phases) in cases {
+            let mut display = Display::new(Mock::default(), size, DisplayRotation::Rotate0);
+            display.init().unwrap();
+            display.iface.commands.clear();
+            display.set_frame_rate(hz).unwrap();
+            assert_eq!(
+                display.iface.commands,
+                [
+                    (CLOCK_DIV, std::vec![clock]),
+                    (PHASE_LENGTH, std::vec![phases])
+                ]
+            );
+            assert_eq!(display.frame_rate(), hz);
+        }
+    }
+
+    #[test]
+    fn brightness_scales_contrast() {
+        const CONTRAST: u8 = 0xC1;
+        const MASTER_CURRENT: u8 = 0xC7;
+
+        // The default channel contrast is 0xC8, 0x8F, 0xC8
+        let cases = [
+            (0, [12, 8, 12], 0),
+            (15, [0xC8, 0x8F, 0xC8], 0),
+            (16, [106, 75, 106], 1),
+            (255, [0xC8, 0x8F, 0xC8], 15),
+        ];
+        let mut display = display();
+        for (brightness, contrast, master) in cases {
+            display.set_brightness(brightness).unwrap();
+            assert_eq!(
+                display.iface.commands,
+                [
+                    (CONTRAST, contrast.to_vec()),
+                    (MASTER_CURRENT, std::vec![master])
+                ]
+            );
+            display.iface.commands.clear();
+        }
+
+        display.set_brightness(16).unwrap();
+        display.iface.commands.clear();
+        display.set_channel_contrast(100, 50, 255).unwrap();
+        assert_eq!(
+            display.iface.commands,
+            [
+                (CONTRAST, std::vec![53, 26, 135]),
+                (MASTER_CURRENT, std::vec![1])
+            ]
+        );
+        assert_eq!(display.get_channel_contrast(), (100, 50, 255));
+        assert_eq!(display.get_brightness(), 16);
+    }
+
+    #[test]
+    fn read_area_pairs_bytes() {
+        const READ_RAM: u8 = 0x5D;
+
+        for data_bus in [DataBus::Parallel8Bit, DataBus::Parallel16Bit] {
+            let mut mock = Mock::default();
+            mock.wide_bus = data_bus == DataBus::Parallel16Bit;
+            for (i, pixel) in mock.ram.iter_mut().enumerate() {
+                *pixel = (i as u16).wrapping_mul(0x0101);
+            }
+            let expected: Vec<u16> = (3..5)
+                .flat_map(|row| (2..5).map(move |column| row * 128 + column))
+                .map(|i: u16| i.wrapping_mul(0x0101))
+                .collect();
+            let mut display =
+                Display::new(mock, DisplaySize::Display128x128, DisplayRotation::Rotate0)
+                    .with_data_bus(data_bus);
+
+            let mut buf = [0; 8];
+            display.read_area((2, 3), (5, 5), &mut buf).unwrap();
+            assert_eq!(buf[..6], expected[..]);
+            assert_eq!(buf[6..], [0, 0]);
+            assert_eq!(
+                display.iface.commands,
+                [
+                    (COLUMN, std::vec![2, 4]),
+                    (ROW, std::vec![3, 4]),
+                    (READ_RAM, Vec::new())
+                ]
+            );
+
+            // The next write sets its window again
+            display.iface.commands.clear();
+            display.set_draw_area((2, 3), (5, 5)).unwrap();
+            assert_eq!(sent(&mut display), [COLUMN, ROW, WRITE_RAM]);
+
+            assert!(matches!(
+                display.read_area((2, 3), (5, 5), &mut buf[..5]),
+                Err(Error::OutOfRange)
+            ));
+        }
     }
 }
@@ -0,0 +1,165 @@
+//! Reduction of 8 bit per channel colours to Rgb565
+
+#[cfg(feature = "graphics")]
+use embedded_graphics_core::{
+    pixelcolor::{raw::RawU16, Gray8, GrayColor, Rgb565, Rgb888, RgbColor},
+    prelude::{Dimensions, DrawTarget, PixelColor, Point, PointsIter},
+    primitives::Rectangle,
+    Pixel,
+};
+
+/// 4x4 Bayer matrix of thresholds from 0 to 15
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// How colours are reduced to the 5, 6 and 5 bits of Rgb565
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dithering {
+    /// Drop the low bits of every channel
+    None,
+    /// Add a threshold from a 4x4 Bayer matrix keyed on the pixel position before dropping the
+    /// low bits, trading banding for a fine regular pattern
+    Ordered,
+}
+
+/// Reduce an 8 bit channel to `bits` bits, adding a threshold from 0 to 15 scaled to the dropped
+/// bits
+fn quantize(value: u8, bits: u32, threshold: u8) -> u16 {
+    let shift = 8 - bits;
+    let offset = ((threshold as u16) << shift) >> 4;
+    ((value as u16 + offset) >> shift).min((1 << bits) - 1)
+}
+
+#[cfg(feature = "buffered")]
+/// Expand a reduced channel back to 8 bits
+pub(crate) fn expand(value: u16, bits: u32) -> u8 {
+    let shift = 8 - bits;
+    ((value << shift) | (value >> (bits - shift))) as u8
+}
+
+/// Convert 8 bit red, green and blue channels to a raw Rgb565 value for the pixel at `x`, `y`
+pub fn to_rgb565(rgb: [u8; 3], x: i32, y: i32, dithering: Dithering) -> u16 {
+    let threshold = match dithering {
+        Dithering::None => 0,
+        Dithering::Ordered => BAYER_4X4[(y & 3) as usize][(x & 3) as usize],
+    };
+    (quantize(rgb[0], 5, threshold) << 11)
+        | (quantize(rgb[1], 6, threshold) << 5)
+        | quantize(rgb[2], 5, threshold)
+}
+
+#[cfg(feature = "graphics")]
+/// Colours which can be drawn through a [`Dithered`] adaptor
+pub trait DitherColor: PixelColor {
+    /// Get the 8 bit red, green and blue channels
+    fn channels(self) -> [u8; 3];
+}
+
+#[cfg(feature = "graphics")]
+impl DitherColor for Rgb888 {
+    fn channels(self) -> [u8; 3] {
+        [self.r(), self.g(), self.b()]
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl DitherColor for Gray8 {
+    fn channels(self) -> [u8; 3] {
+        [self.luma(); 3]
+    }
+}
+
+#[cfg(feature = "graphics")]
+/// A `DrawTarget` for `Rgb888` or `Gray8` colours on top of an Rgb565 target such as
+/// [`GraphicsMode`](../mode/graphics/struct.GraphicsMode.html), converting every colour with
+/// optional ordered dithering
+pub struct Dithered<'a, T, C> {
+    target: &'a mut T,
+    dithering: Dithering,
+    color: core::marker::PhantomData<C>,
+}
+
+#[cfg(feature = "graphics")]
+impl<'a, T, C> Dithered<'a, T, C>
+where
+    T: DrawTarget<Color = Rgb565>,
+    C: DitherColor,
+{
+    /// Draw `C` colours to `target`
+    pub fn new(target: &'a mut T, dithering: Dithering) -> Self {
+        Dithered {
+            target,
+            dithering,
+            color: core::marker::PhantomData,
+        }
+    }
+
+    /// Get the dithering applied to drawn colours
+    pub fn dithering(&self) -> Dithering {
+        self.dithering
+    }
+
+    /// Set the dithering applied to drawn colours
+    pub fn set_dithering(&mut self, dithering: Dithering) {
+        self.dithering = dithering;
+    }
+
+    /// Convert a colour drawn at `point`
+    fn convert(&self, point: Point, color: C) -> Rgb565 {
+        let raw = to_rgb565(color.channels(), point.x, point.y, self.dithering);
+        Rgb565::from(RawU16::new(raw))
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<T, C> Dimensions for Dithered<'_, T, C>
+where
+    T: DrawTarget<Color = Rgb565>,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<T, C> DrawTarget for Dithered<'_, T, C>
+where
+    T: DrawTarget<Color = Rgb565>,
+    C: DitherColor,
+{
+    type Color = C;
+    type Error = T::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let dithering = self.dithering;
+        self.target
+            .draw_iter(pixels.into_iter().map(|Pixel(point, color)| {
+                let raw = to_rgb565(color.channels(), point.x, point.y, dithering);
+                Pixel(point, Rgb565::from(RawU16::new(raw)))
+            }))
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let dithering = self.dithering;
+        let colors = area.points().zip(colors).map(|(point, color)| {
+            let raw = to_rgb565(color.channels(), point.x, point.y, dithering);
+            Rgb565::from(RawU16::new(raw))
+        });
+        self.target.fill_contiguous(area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        match self.dithering {
+            Dithering::None => {
+                let color = self.convert(Point::zero(), color);
+                self.target.fill_solid(area, color)
+            }
+            Dithering::Ordered => self.fill_contiguous(area, area.points().map(|_| color)),
+        }
+    }
+}
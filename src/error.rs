@@ -0,0 +1,22 @@
+//! Driver errors
+
+use display_interface::DisplayError;
+
+/// Errors returned by the driver
+#[derive(Clone, Debug)]
+pub enum Error {
+    /// The display interface failed to transfer data
+    Interface(DisplayError),
+    /// The MCU interface of the controller is locked, only unlocking it is accepted
+    InterfaceLocked,
+    /// The command is protected and protected commands are locked
+    CommandLocked,
+    /// A command parameter is outside of the range supported by the controller
+    OutOfRange,
+}
+
+impl From<DisplayError> for Error {
+    fn from(error: DisplayError) -> Self {
+        Error::Interface(error)
+    }
+}
@@ -0,0 +1,65 @@
+//! Brightness transitions
+
+/// A linear transition from one brightness to another over a fixed duration.
+///
+/// A `Fade` holds no reference to the display, it only maps the time elapsed since the start of the
+/// transition to a brightness. Blocking fades are done with
+/// [`GraphicsMode::fade_to`](../mode/graphics/struct.GraphicsMode.html#method.fade_to), without
+/// blocking by calling
+/// [`GraphicsMode::update_fade`](../mode/graphics/struct.GraphicsMode.html#method.update_fade)
+/// from a main loop or timer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fade {
+    from: u8,
+    to: u8,
+    duration_ms: u32,
+}
+
+impl Fade {
+    /// Create a fade from one brightness to another taking `duration_ms` milliseconds
+    pub fn new(from: u8, to: u8, duration_ms: u32) -> Self {
+        Fade {
+            from,
+            to,
+            duration_ms,
+        }
+    }
+
+    /// Get the brightness the fade starts at
+    pub fn start(&self) -> u8 {
+        self.from
+    }
+
+    /// Get the brightness the fade ends at
+    pub fn target(&self) -> u8 {
+        self.to
+    }
+
+    /// Get the duration of the fade in milliseconds
+    pub fn duration_ms(&self) -> u32 {
+        self.duration_ms
+    }
+
+    /// Get the brightness `elapsed_ms` milliseconds after the start of the fade
+    pub fn brightness_at(&self, elapsed_ms: u32) -> u8 {
+        if self.is_done(elapsed_ms) {
+            return self.to;
+        }
+
+        let from = self.from as i32;
+        let delta = self.to as i32 - from;
+        let step = delta as i64 * elapsed_ms as i64 / self.duration_ms as i64;
+
+        (from + step as i32) as u8
+    }
+
+    /// Whether the fade has reached its target brightness after `elapsed_ms` milliseconds
+    pub fn is_done(&self, elapsed_ms: u32) -> bool {
+        elapsed_ms >= self.duration_ms
+    }
+
+    /// Number of brightness levels the fade passes through
+    pub(crate) fn steps(&self) -> u32 {
+        self.from.abs_diff(self.to) as u32
+    }
+}
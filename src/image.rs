@@ -0,0 +1,47 @@
+//! Images stored as raw display data
+
+use crate::sprite::Sprite;
+
+/// A block of big endian Rgb565 pixels, stored row by row, e.g. converted at compile time with
+/// `include_image!` from the `macros` feature. Draw it with
+/// [`GraphicsMode::draw_image`](../mode/graphics/struct.GraphicsMode.html#method.draw_image).
+#[derive(Clone, Copy, Debug)]
+pub struct Image<'a> {
+    width: u32,
+    height: u32,
+    data: &'a [u8],
+}
+
+impl<'a> Image<'a> {
+    /// Create a `width` by `height` image from big endian Rgb565 data. Panics, or fails to
+    /// compile in a constant, if `data` is shorter than the image.
+    pub const fn new(width: u32, height: u32, data: &'a [u8]) -> Self {
+        assert!(data.len() >= width as usize * height as usize * 2);
+        Image {
+            width,
+            height,
+            data,
+        }
+    }
+
+    /// Get the width of the image
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height of the image
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get the pixel data
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Use the image as a sprite in which pixels of colour `key` are transparent
+    pub fn sprite(&self, key: u16) -> Sprite<'a> {
+        // The image data is long enough by construction
+        Sprite::new(self.data, self.width, self.height, key).unwrap()
+    }
+}
@@ -0,0 +1,254 @@
+//! Display interfaces not covered by the `display-interface` crates
+
+use core::convert::Infallible;
+
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use hal::digital::{ErrorType, OutputPin};
+use hal::spi::SpiDevice;
+
+/// Placeholder reset pin for displays whose RST pin is not controlled by the driver
+pub struct NoResetPin;
+
+impl ErrorType for NoResetPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for NoResetPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Display interface which can also read data back from the controller, as 6800 and 8080 parallel
+/// interfaces can. The serial interfaces of the SSD1351 are write only.
+pub trait ReadDataCommand: WriteOnlyDataCommand {
+    /// Read words from the data bus, one per bus cycle. On an 8 bit bus the byte read is stored
+    /// in the lower 8 bits of each word.
+    fn read_data(&mut self, buf: &mut [u16]) -> Result<(), DisplayError>;
+}
+
+/// Number of 9 bit words packed into the transfer buffer, a multiple of 8 so every full buffer
+/// ends on a byte boundary
+const WORDS_PER_TRANSFER: usize = 64;
+
+/// 3-wire (9 bit) SPI interface.
+///
+/// In 3-wire mode the controller has no D/C# pin, instead every byte is preceded by a D/C# bit
+/// (0 for commands, 1 for data), giving 9 bit words which are packed MSB first into the bytes
+/// sent over the SPI bus. Bulk data is packed and sent in transactions of 64 words, which always
+/// end on a byte boundary. When the words of the final transaction don't fill its last byte it is
+/// padded with zeroes, which the controller drops as an incomplete word when chip select is
+/// deasserted.
+///
+/// Use with [`SSD1351_SPI_MODE`](crate::prelude::SSD1351_SPI_MODE) and a serial data bus.
+pub struct ThreeWireSPIInterface<SPI> {
+    spi: SPI,
+}
+
+impl<SPI> ThreeWireSPIInterface<SPI>
+where
+    SPI: SpiDevice,
+{
+    /// Create new 3-wire SPI interface for communication with the display
+    pub fn new(spi: SPI) -> Self {
+        Self { spi }
+    }
+
+    /// Consume the interface and release the SPI device
+    pub fn release(self) -> SPI {
+        self.spi
+    }
+
+    /// Pack the bytes into 9 bit words with the given D/C# bit and send them
+    fn send<I>(&mut self, dc: bool, bytes: I) -> Result<(), DisplayError>
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        let mut buf = [0u8; WORDS_PER_TRANSFER * 9 / 8];
+        let mut packer = Packer::default();
+
+        let mut bytes = bytes.into_iter().peekable();
+        while bytes.peek().is_some() {
+            let mut len = 0;
+            for byte in bytes.by_ref().take(WORDS_PER_TRANSFER) {
+                len += packer.push((dc as u16) << 8 | byte as u16, &mut buf[len..]);
+            }
+            len += packer.finish(&mut buf[len..]);
+            self.spi
+                .write(&buf[..len])
+                .map_err(|_| DisplayError::BusWriteError)?;
+        }
+
+        Ok(())
+    }
+
+    /// Send data in any of the supported formats with the given D/C# bit
+    fn send_format(&mut self, dc: bool, data: DataFormat<'_>) -> Result<(), DisplayError> {
+        match data {
+            DataFormat::U8(slice) => self.send(dc, slice.iter().copied()),
+            DataFormat::U16(slice) => self.send(dc, slice.iter().flat_map(|w| w.to_ne_bytes())),
+            DataFormat::U16BE(slice) => self.send(dc, slice.iter().flat_map(|w| w.to_be_bytes())),
+            DataFormat::U16LE(slice) => self.send(dc, slice.iter().flat_map(|w| w.to_le_bytes())),
+            DataFormat::U8Iter(iter) => self.send(dc, iter),
+            DataFormat::U16BEIter(iter) => self.send(dc, iter.flat_map(|w| w.to_be_bytes())),
+            DataFormat::U16LEIter(iter) => self.send(dc, iter.flat_map(|w| w.to_le_bytes())),
+            _ => Err(DisplayError::DataFormatNotImplemented),
+        }
+    }
+}
+
+impl<SPI> WriteOnlyDataCommand for ThreeWireSPIInterface<SPI>
+where
+    SPI: SpiDevice,
+{
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.send_format(false, cmds)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.send_format(true, buf)
+    }
+}
+
+/// Bit accumulator packing 9 bit words MSB first into bytes
+#[derive(Default)]
+struct Packer {
+    bits: u32,
+    len: u32,
+}
+
+impl Packer {
+    /// Add a word, writing the completed bytes to `out` and returning how many were written
+    fn push(&mut self, word: u16, out: &mut [u8]) -> usize {
+        self.bits = self.bits << 9 | word as u32;
+        self.len += 9;
+        let mut written = 0;
+        while self.len >= 8 {
+            self.len -= 8;
+            out[written] = (self.bits >> self.len) as u8;
+            written += 1;
+        }
+        written
+    }
+
+    /// Write the remaining bits padded with zeroes, returning how many bytes were written
+    fn finish(&mut self, out: &mut [u8]) -> usize {
+        if self.len == 0 {
+            return 0;
+        }
+        out[0] = (self.bits << (8 - self.len)) as u8;
+        self.len = 0;
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use hal::spi::{ErrorKind, ErrorType as SpiErrorType, Operation};
+    use std::vec::Vec;
+
+    /// SPI device recording the bytes written in every transaction
+    #[derive(Default)]
+    struct Spi {
+        transactions: Vec<Vec<u8>>,
+    }
+
+    impl SpiErrorType for Spi {
+        type Error = ErrorKind;
+    }
+
+    impl SpiDevice for Spi {
+        fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), ErrorKind> {
+            let mut written = Vec::new();
+            for operation in operations {
+                match operation {
+                    Operation::Write(bytes) => written.extend_from_slice(bytes),
+                    _ => return Err(ErrorKind::Other),
+                }
+            }
+            self.transactions.push(written);
+            Ok(())
+        }
+    }
+
+    /// Unpack the 9 bit words of a transaction, checking the padding of its last byte is zero
+    fn unpack(bytes: &[u8]) -> Vec<u16> {
+        let bits = bytes.len() * 8;
+        let bit = |i: usize| (bytes[i / 8] >> (7 - i % 8)) as u16 & 1;
+        let words = (0..bits / 9)
+            .map(|word| (0..9).fold(0, |acc, i| acc << 1 | bit(word * 9 + i)))
+            .collect();
+        assert!(
+            (bits / 9 * 9..bits).all(|i| bit(i) == 0),
+            "padding is not zero"
+        );
+        words
+    }
+
+    #[test]
+    fn packs_msb_first() {
+        let mut packer = Packer::default();
+        let mut out = [0; 4];
+        assert_eq!(packer.push(0x155, &mut out), 1);
+        assert_eq!(out[0], 0xAA);
+        assert_eq!(packer.finish(&mut out), 1);
+        assert_eq!(out[0], 0x80);
+        assert_eq!(packer.finish(&mut out), 0);
+
+        // Eight words end on a byte boundary
+        let mut out = [0; 9];
+        let mut len = 0;
+        for _ in 0..8 {
+            len += packer.push(0x1FF, &mut out[len..]);
+        }
+        assert_eq!(len, 9);
+        assert_eq!(packer.finish(&mut out[len..]), 0);
+        assert_eq!(out, [0xFF; 9]);
+    }
+
+    #[test]
+    fn dc_bit() {
+        let mut interface = ThreeWireSPIInterface::new(Spi::default());
+        interface.send_commands(DataFormat::U8(&[0x55])).unwrap();
+        interface.send_data(DataFormat::U8(&[0x55])).unwrap();
+        let spi = interface.release();
+        assert_eq!(spi.transactions, [[0x2A, 0x80], [0xAA, 0x80]]);
+    }
+
+    #[test]
+    fn chunks_of_64_words() {
+        for (count, transactions) in [(1, [2].as_slice()), (8, &[9]), (64, &[72]), (65, &[72, 2])] {
+            let data: Vec<u8> = (0..count).map(|i| (i * 7) as u8).collect();
+            let mut interface = ThreeWireSPIInterface::new(Spi::default());
+            interface.send_data(DataFormat::U8(&data)).unwrap();
+            let spi = interface.release();
+            let lengths: Vec<usize> = spi.transactions.iter().map(Vec::len).collect();
+            assert_eq!(lengths, transactions, "{} words", count);
+
+            let words: Vec<u16> = spi.transactions.iter().flat_map(|t| unpack(t)).collect();
+            let expected: Vec<u16> = data.iter().map(|&byte| 0x100 | byte as u16).collect();
+            assert_eq!(words, expected, "{} words", count);
+        }
+    }
+
+    #[test]
+    fn word_formats() {
+        let mut interface = ThreeWireSPIInterface::new(Spi::default());
+        interface
+            .send_data(DataFormat::U16BEIter(&mut [0x1234u16, 0xABCD].into_iter()))
+            .unwrap();
+        interface
+            .send_data(DataFormat::U16LE(&mut [0x1234]))
+            .unwrap();
+        let spi = interface.release();
+        assert_eq!(unpack(&spi.transactions[0]), [0x112, 0x134, 0x1AB, 0x1CD]);
+        assert_eq!(unpack(&spi.transactions[1]), [0x134, 0x112]);
+    }
+}
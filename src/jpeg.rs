@@ -0,0 +1,869 @@
+//! Decoding of baseline JPEG images in strips
+//!
+//! Images are decoded one row of MCUs (minimum coded units, 8 or 16 rows of pixels) at a time,
+//! optionally scaled down by 2, 4 or 8, and only the part that is drawn is kept. A strip of at
+//! most 128 by 16 pixels and the tables of the image are all the memory decoding takes, see
+//! [`GraphicsMode::draw_jpeg`](../mode/graphics/struct.GraphicsMode.html#method.draw_jpeg).
+//!
+//! Supported are 8 bit baseline and extended sequential Huffman coded images, either greyscale
+//! or YCbCr with sampling factors of 1 or 2, and restart intervals. Progressive and arithmetic
+//! coded images are not.
+
+use crate::dither::{to_rgb565, Dithering};
+use crate::error::Error;
+
+/// Widest strip a decoder keeps
+const MAX_STRIP_WIDTH: usize = 128;
+
+/// Natural order index of the coefficients in zigzag order
+const ZIGZAG: [u8; 64] = [
+    0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20,
+    13, 6, 7, 14, 21, 28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59,
+    52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55, 62, 63,
+];
+
+/// Errors of JPEG decoding
+#[derive(Clone, Debug)]
+pub enum JpegError {
+    /// The image uses a JPEG feature the decoder does not support
+    Unsupported,
+    /// The data is not a valid JPEG image or ends early
+    Malformed,
+    /// Drawing the image failed
+    Display(Error),
+}
+
+impl From<Error> for JpegError {
+    fn from(error: Error) -> Self {
+        JpegError::Display(error)
+    }
+}
+
+/// Reduction applied while decoding
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scale {
+    /// Full size
+    Full,
+    /// Half the width and height
+    Half,
+    /// A quarter of the width and height
+    Quarter,
+    /// An eighth of the width and height, only the DC coefficient of every block is used
+    Eighth,
+}
+
+impl Scale {
+    /// Get the log2 of the reduction
+    fn shift(self) -> u32 {
+        match self {
+            Scale::Full => 0,
+            Scale::Half => 1,
+            Scale::Quarter => 2,
+            Scale::Eighth => 3,
+        }
+    }
+}
+
+/// Canonical Huffman table
+#[derive(Clone, Copy, Debug)]
+struct Huffman<'a> {
+    values: &'a [u8],
+    /// Largest code of every length, -1 if there is none
+    maxcode: [i32; 17],
+    /// Index of the value of a code of every length minus the code
+    offset: [i32; 17],
+}
+
+impl<'a> Huffman<'a> {
+    const EMPTY: Self = Huffman {
+        values: &[],
+        maxcode: [-1; 17],
+        offset: [0; 17],
+    };
+
+    /// Build a table from the number of codes of every length and their values
+    fn new(counts: &[u8], values: &'a [u8]) -> Result<Self, JpegError> {
+        let mut table = Huffman {
+            values,
+            ..Self::EMPTY
+        };
+        let mut code = 0i32;
+        let mut index = 0i32;
+        for (len, &count) in (1..).zip(counts) {
+            if count > 0 {
+                table.offset[len] = index - code;
+                code += count as i32;
+                index += count as i32;
+                if code > 1 << len {
+                    return Err(JpegError::Malformed);
+                }
+                table.maxcode[len] = code - 1;
+            }
+            code <<= 1;
+        }
+        Ok(table)
+    }
+
+    /// Decode the next value
+    fn decode(&self, reader: &mut BitReader) -> Result<u8, JpegError> {
+        let peek = reader.peek(16) as i32;
+        for len in 1..=16 {
+            let code = peek >> (16 - len);
+            if code <= self.maxcode[len] {
+                reader.consume(len as u32);
+                let index = (code + self.offset[len]) as usize;
+                return self.values.get(index).copied().ok_or(JpegError::Malformed);
+            }
+        }
+        Err(JpegError::Malformed)
+    }
+}
+
+/// Reader of entropy coded data, removing stuffed bytes and stopping at markers
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    /// Buffered bits, most significant first
+    bits: u32,
+    count: u32,
+    /// A marker was reached, zeros are read from here on
+    marker: bool,
+    /// The data ended without a marker, the image is truncated
+    truncated: bool,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        BitReader {
+            data,
+            pos: 0,
+            bits: 0,
+            count: 0,
+            marker: false,
+            truncated: false,
+        }
+    }
+
+    fn fill(&mut self) {
+        while self.count <= 24 {
+            let mut byte = 0;
+            if !self.marker {
+                match (self.data.get(self.pos), self.data.get(self.pos + 1)) {
+                    (None, _) | (Some(0xFF), None) => self.truncated = true,
+                    (Some(0xFF), Some(0)) => {
+                        byte = 0xFF;
+                        self.pos += 2;
+                    }
+                    (Some(0xFF), Some(_)) => self.marker = true,
+                    (Some(&value), _) => {
+                        byte = value;
+                        self.pos += 1;
+                    }
+                }
+            }
+            self.bits |= (byte as u32) << (24 - self.count);
+            self.count += 8;
+        }
+    }
+
+    /// Look at the next `n` bits, 1 to 16
+    fn peek(&mut self, n: u32) -> u32 {
+        self.fill();
+        self.bits >> (32 - n)
+    }
+
+    fn consume(&mut self, n: u32) {
+        self.bits <<= n;
+        self.count -= n;
+    }
+
+    /// Read a coefficient of `size` bits, 0 to 16
+    fn receive_extend(&mut self, size: u8) -> i32 {
+        if size == 0 {
+            return 0;
+        }
+        let size = size as u32;
+        let value = self.peek(size) as i32;
+        self.consume(size);
+        if value < 1 << (size - 1) {
+            value - (1 << size) + 1
+        } else {
+            value
+        }
+    }
+
+    /// Skip to the data after the next restart marker
+    fn restart(&mut self) -> Result<(), JpegError> {
+        while self.pos + 1 < self.data.len() {
+            if self.data[self.pos] == 0xFF && (0xD0..=0xD7).contains(&self.data[self.pos + 1]) {
+                self.pos += 2;
+                self.bits = 0;
+                self.count = 0;
+                self.marker = false;
+                return Ok(());
+            }
+            self.pos += 1;
+        }
+        Err(JpegError::Malformed)
+    }
+}
+
+/// A colour component of the frame
+#[derive(Clone, Copy, Debug, Default)]
+struct Component {
+    id: u8,
+    h: usize,
+    v: usize,
+    quant: usize,
+    dc: usize,
+    ac: usize,
+}
+
+/// A JPEG image in memory, with its tables parsed
+#[derive(Clone, Debug)]
+pub struct JpegImage<'a> {
+    width: u32,
+    height: u32,
+    components: [Component; 3],
+    count: usize,
+    quant: [[u16; 64]; 4],
+    dc: [Huffman<'a>; 2],
+    ac: [Huffman<'a>; 2],
+    restart_interval: u16,
+    scan: &'a [u8],
+}
+
+impl<'a> JpegImage<'a> {
+    /// Parse the headers of a JPEG file up to its image data
+    pub fn new(data: &'a [u8]) -> Result<Self, JpegError> {
+        if data.get(..2) != Some(&[0xFF, 0xD8]) {
+            return Err(JpegError::Malformed);
+        }
+        let mut image = JpegImage {
+            width: 0,
+            height: 0,
+            components: [Component::default(); 3],
+            count: 0,
+            quant: [[0; 64]; 4],
+            dc: [Huffman::EMPTY; 2],
+            ac: [Huffman::EMPTY; 2],
+            restart_interval: 0,
+            scan: &[],
+        };
+        let mut quant_defined = 0u8;
+        let mut pos = 2;
+        loop {
+            // Markers may be padded with any number of fill bytes
+            while data.get(pos + 1) == Some(&0xFF) {
+                pos += 1;
+            }
+            let header = data.get(pos..pos + 4).ok_or(JpegError::Malformed)?;
+            if header[0] != 0xFF {
+                return Err(JpegError::Malformed);
+            }
+            let marker = header[1];
+            let len = u16::from_be_bytes([header[2], header[3]]) as usize;
+            let segment = data
+                .get(pos + 4..pos + 2 + len.max(2))
+                .ok_or(JpegError::Malformed)?;
+            pos += 2 + len;
+
+            match marker {
+                // Quantization tables
+                0xDB => {
+                    let mut rest = segment;
+                    while let Some((&info, tail)) = rest.split_first() {
+                        let (precision, id) = (info >> 4, (info & 0x0F) as usize);
+                        let size = if precision == 0 { 64 } else { 128 };
+                        if id > 3 || precision > 1 || tail.len() < size {
+                            return Err(JpegError::Malformed);
+                        }
+                        for (k, value) in image.quant[id].iter_mut().enumerate() {
+                            *value = match precision {
+                                0 => tail[k] as u16,
+                                _ => u16::from_be_bytes([tail[k * 2], tail[k * 2 + 1]]),
+                            };
+                        }
+                        quant_defined |= 1 << id;
+                        rest = &tail[size..];
+                    }
+                }
+                // Huffman tables
+                0xC4 => {
+                    let mut rest = segment;
+                    while let Some((&info, tail)) = rest.split_first() {
+                        let (class, id) = (info >> 4, (info & 0x0F) as usize);
+                        let counts = tail.get(..16).ok_or(JpegError::Malformed)?;
+                        let total = counts.iter().map(|&count| count as usize).sum::<usize>();
+                        let values = tail.get(16..16 + total).ok_or(JpegError::Malformed)?;
+                        let table = Huffman::new(counts, values)?;
+                        match (class, id) {
+                            (0, 0..=1) => image.dc[id] = table,
+                            (1, 0..=1) => image.ac[id] = table,
+                            (0..=1, _) => return Err(JpegError::Unsupported),
+                            _ => return Err(JpegError::Malformed),
+                        }
+                        rest = &tail[16 + total..];
+                    }
+                }
+                // Baseline and extended sequential frames
+                0xC0 | 0xC1 => {
+                    let frame = segment.get(..6).ok_or(JpegError::Malformed)?;
+                    image.height = u16::from_be_bytes([frame[1], frame[2]]) as u32;
+                    image.width = u16::from_be_bytes([frame[3], frame[4]]) as u32;
+                    image.count = frame[5] as usize;
+                    if frame[0] != 8 || image.height == 0 || !matches!(image.count, 1 | 3) {
+                        return Err(JpegError::Unsupported);
+                    }
+                    let specs = segment
+                        .get(6..6 + image.count * 3)
+                        .ok_or(JpegError::Malformed)?;
+                    for (component, spec) in image.components.iter_mut().zip(specs.chunks(3)) {
+                        component.id = spec[0];
+                        component.h = (spec[1] >> 4) as usize;
+                        component.v = (spec[1] & 0x0F) as usize;
+                        component.quant = (spec[2] & 0x03) as usize;
+                        if !(1..=2).contains(&component.h) || !(1..=2).contains(&component.v) {
+                            return Err(JpegError::Unsupported);
+                        }
+                    }
+                    if image.count == 1 {
+                        // A single component is never interleaved
+                        image.components[0].h = 1;
+                        image.components[0].v = 1;
+                    }
+                }
+                // Progressive, lossless, hierarchical and arithmetic coded frames
+                0xC2 | 0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF => {
+                    return Err(JpegError::Unsupported);
+                }
+                // Restart interval
+                0xDD => {
+                    let interval = segment.get(..2).ok_or(JpegError::Malformed)?;
+                    image.restart_interval = u16::from_be_bytes([interval[0], interval[1]]);
+                }
+                // Start of scan
+                0xDA => {
+                    let count = *segment.first().ok_or(JpegError::Malformed)? as usize;
+                    if image.count == 0 {
+                        return Err(JpegError::Malformed);
+                    }
+                    if count != image.count {
+                        // Components coded in separate scans
+                        return Err(JpegError::Unsupported);
+                    }
+                    let specs = segment.get(1..1 + count * 2).ok_or(JpegError::Malformed)?;
+                    for spec in specs.chunks(2) {
+                        let component = image.components[..image.count]
+                            .iter_mut()
+                            .find(|component| component.id == spec[0])
+                            .ok_or(JpegError::Malformed)?;
+                        component.dc = (spec[1] >> 4) as usize;
+                        component.ac = (spec[1] & 0x0F) as usize;
+                        if component.dc > 1 || component.ac > 1 {
+                            return Err(JpegError::Unsupported);
+                        }
+                        if quant_defined & (1 << component.quant) == 0 {
+                            return Err(JpegError::Malformed);
+                        }
+                    }
+                    image.scan = data.get(pos..).ok_or(JpegError::Malformed)?;
+                    return Ok(image);
+                }
+                // End of image before any scan
+                0xD9 => return Err(JpegError::Malformed),
+                // Application data, comments and others
+                _ => {}
+            }
+        }
+    }
+
+    /// Get the width of the image
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height of the image
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get the size of the image decoded with `scale`, rounded up
+    pub fn scaled_size(&self, scale: Scale) -> (u32, u32) {
+        let shift = scale.shift();
+        let round = (1 << shift) - 1;
+        (
+            (self.width + round) >> shift,
+            (self.height + round) >> shift,
+        )
+    }
+
+    /// Get the smallest reduction with which the image fits into `width` by `height`, if any
+    pub fn scale_to_fit(&self, width: u32, height: u32) -> Option<Scale> {
+        [Scale::Full, Scale::Half, Scale::Quarter, Scale::Eighth]
+            .into_iter()
+            .find(|&scale| {
+                let (scaled_width, scaled_height) = self.scaled_size(scale);
+                scaled_width <= width && scaled_height <= height
+            })
+    }
+
+    /// Decode the image with `scale`, keeping the columns and rows in the given ranges of the
+    /// scaled image. After every row of MCUs `strip` gets the first kept row of the strip, the
+    /// number of rows and their Rgb565 pixels. At most 128 columns are kept.
+    pub(crate) fn decode<F>(
+        &self,
+        scale: Scale,
+        columns: (u32, u32),
+        rows: (u32, u32),
+        mut strip: F,
+    ) -> Result<(), JpegError>
+    where
+        F: FnMut(u32, u32, &[u16]) -> Result<(), JpegError>,
+    {
+        let shift = scale.shift();
+        let block = 8 >> shift;
+        let components = &self.components[..self.count];
+        let h_max = components.iter().map(|c| c.h).max().unwrap_or(1);
+        let v_max = components.iter().map(|c| c.v).max().unwrap_or(1);
+        let (mcu_width, mcu_height) = (8 * h_max as u32, 8 * v_max as u32);
+        let mcus_x = self.width.div_ceil(mcu_width);
+        let mcus_y = self.height.div_ceil(mcu_height);
+        // Size of an MCU after scaling
+        let (scaled_width, scaled_height) = (mcu_width >> shift, mcu_height >> shift);
+
+        let (left, right) = columns;
+        let (top, bottom) = rows;
+        let kept = right.saturating_sub(left) as usize;
+        if kept > MAX_STRIP_WIDTH {
+            return Err(JpegError::Unsupported);
+        }
+
+        let mut pixels = [0u16; MAX_STRIP_WIDTH * 16];
+        // Samples of every component of an MCU, up to 2 by 2 blocks
+        let mut samples = [[0u8; 256]; 3];
+        let mut coefficients = [0i32; 64];
+        let mut predictions = [0i32; 3];
+        let mut reader = BitReader::new(self.scan);
+        let interval = self.restart_interval;
+        let mut until_restart = interval;
+
+        for mcu_y in 0..mcus_y {
+            let y0 = mcu_y * scaled_height;
+            if y0 >= bottom {
+                break;
+            }
+            for mcu_x in 0..mcus_x {
+                if interval > 0 {
+                    if until_restart == 0 {
+                        reader.restart()?;
+                        predictions = [0; 3];
+                        until_restart = interval;
+                    }
+                    until_restart -= 1;
+                }
+
+                let x0 = mcu_x * scaled_width;
+                let visible = y0 + scaled_height > top && x0 < right && x0 + scaled_width > left;
+                for (i, component) in components.iter().enumerate() {
+                    let stride = component.h * block;
+                    for block_y in 0..component.v {
+                        for block_x in 0..component.h {
+                            self.decode_block(
+                                &mut reader,
+                                component,
+                                &mut predictions[i],
+                                &mut coefficients,
+                            )?;
+                            if visible {
+                                let start = block_y * block * stride + block_x * block;
+                                idct(&coefficients, shift, &mut samples[i][start..], stride);
+                            }
+                        }
+                    }
+                }
+                if reader.truncated {
+                    return Err(JpegError::Malformed);
+                }
+                if !visible {
+                    continue;
+                }
+
+                // Upsample and convert the visible pixels of the MCU
+                for y in 0..scaled_height {
+                    let row = y0 + y;
+                    if row < top || row >= bottom {
+                        continue;
+                    }
+                    for x in 0..scaled_width {
+                        let column = x0 + x;
+                        if column < left || column >= right {
+                            continue;
+                        }
+                        let mut ycc = [0u8; 3];
+                        for (i, component) in components.iter().enumerate() {
+                            let sx = x as usize * component.h / h_max;
+                            let sy = y as usize * component.v / v_max;
+                            ycc[i] = samples[i][sy * component.h * block + sx];
+                        }
+                        let rgb = if self.count == 1 {
+                            [ycc[0]; 3]
+                        } else {
+                            ycc_to_rgb(ycc)
+                        };
+                        pixels[y as usize * kept + (column - left) as usize] =
+                            to_rgb565(rgb, column as i32, row as i32, Dithering::None);
+                    }
+                }
+            }
+
+            let first = y0.max(top);
+            let last = (y0 + scaled_height).min(bottom);
+            if first < last {
+                let start = (first - y0) as usize * kept;
+                let end = (last - y0) as usize * kept;
+                strip(first, last - first, &pixels[start..end])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Decode and dequantize the next block of a component in natural order
+    fn decode_block(
+        &self,
+        reader: &mut BitReader,
+        component: &Component,
+        prediction: &mut i32,
+        coefficients: &mut [i32; 64],
+    ) -> Result<(), JpegError> {
+        // Beyond the range of 8 bit samples, keeps the IDCT from overflowing
+        let dequantize =
+            |value: i32, quant: u16| value.saturating_mul(quant as i32).clamp(-8192, 8191);
+        let quant = &self.quant[component.quant];
+        let ac = &self.ac[component.ac];
+        *coefficients = [0; 64];
+
+        let size = self.dc[component.dc].decode(reader)?;
+        if size > 16 {
+            return Err(JpegError::Malformed);
+        }
+        *prediction = prediction.wrapping_add(reader.receive_extend(size));
+        coefficients[0] = dequantize(*prediction, quant[0]);
+
+        let mut k = 1;
+        while k < 64 {
+            let symbol = ac.decode(reader)?;
+            let (run, size) = ((symbol >> 4) as usize, symbol & 0x0F);
+            if size == 0 {
+                if run != 15 {
+                    // End of block
+                    break;
+                }
+                k += 16;
+                continue;
+            }
+            k += run;
+            if k > 63 {
+                return Err(JpegError::Malformed);
+            }
+            coefficients[ZIGZAG[k] as usize] = dequantize(reader.receive_extend(size), quant[k]);
+            k += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Convert a JFIF YCbCr sample to red, green and blue
+fn ycc_to_rgb([y, cb, cr]: [u8; 3]) -> [u8; 3] {
+    let y = ((y as i32) << 16) + (1 << 15);
+    let (cb, cr) = (cb as i32 - 128, cr as i32 - 128);
+    let clamp = |value: i32| (value >> 16).clamp(0, 255) as u8;
+    [
+        clamp(y + 91881 * cr),
+        clamp(y - 22553 * cb - 46802 * cr),
+        clamp(y + 116130 * cb),
+    ]
+}
+
+/// One dimensional IDCT in fixed point with 12 fractional bits, giving the even and odd parts
+fn idct_1d(s: [i32; 8]) -> ([i32; 4], [i32; 4]) {
+    let p1 = (s[2] + s[6]) * 2217;
+    let t2 = p1 + s[6] * -7567;
+    let t3 = p1 + s[2] * 3135;
+    let t0 = (s[0] + s[4]) << 12;
+    let t1 = (s[0] - s[4]) << 12;
+    let even = [t0 + t3, t1 + t2, t1 - t2, t0 - t3];
+
+    let (t0, t1, t2, t3) = (s[7], s[5], s[3], s[1]);
+    let p3 = t0 + t2;
+    let p4 = t1 + t3;
+    let p1 = t0 + t3;
+    let p2 = t1 + t2;
+    let p5 = (p3 + p4) * 4816;
+    let p1 = p5 + p1 * -3685;
+    let p2 = p5 + p2 * -10497;
+    let p3 = p3 * -8034;
+    let p4 = p4 * -1597;
+    let odd = [
+        t3 * 6149 + p1 + p4,
+        t2 * 12586 + p2 + p3,
+        t1 * 8410 + p2 + p4,
+        t0 * 1223 + p1 + p3,
+    ];
+    (even, odd)
+}
+
+/// Inverse transform a block into `8 >> shift` by `8 >> shift` samples at the start of `out`,
+/// averaging the full size samples when scaling
+fn idct(coefficients: &[i32; 64], shift: u32, out: &mut [u8], stride: usize) {
+    if shift == 3 {
+        out[0] = (((coefficients[0] + 4) >> 3) + 128).clamp(0, 255) as u8;
+        return;
+    }
+
+    // Columns, keeping 2 extra bits of precision
+    let mut columns = [0i32; 64];
+    for x in 0..8 {
+        let s: [i32; 8] = core::array::from_fn(|y| coefficients[y * 8 + x]);
+        if s[1..].iter().all(|&c| c == 0) {
+            for y in 0..8 {
+                columns[y * 8 + x] = s[0] << 2;
+            }
+            continue;
+        }
+        let (even, odd) = idct_1d(s);
+        for i in 0..4 {
+            columns[i * 8 + x] = (even[i] + odd[i] + 512) >> 10;
+            columns[(7 - i) * 8 + x] = (even[i] - odd[i] + 512) >> 10;
+        }
+    }
+
+    // Rows, removing the scaling and adding the level shift
+    let mut samples = [0u8; 64];
+    for y in 0..8 {
+        let s: [i32; 8] = core::array::from_fn(|x| columns[y * 8 + x]);
+        let (even, odd) = idct_1d(s);
+        let bias = (1 << 16) + (128 << 17);
+        let clamp = |value: i32| (value >> 17).clamp(0, 255) as u8;
+        for i in 0..4 {
+            samples[y * 8 + i] = clamp(even[i] + odd[i] + bias);
+            samples[y * 8 + 7 - i] = clamp(even[i] - odd[i] + bias);
+        }
+    }
+
+    let size = 8 >> shift;
+    let area = 1u32 << (shift * 2);
+    for y in 0..size {
+        for x in 0..size {
+            let mut sum = area / 2;
+            for sy in y << shift..(y + 1) << shift {
+                for sx in x << shift..(x + 1) << shift {
+                    sum += samples[sy * 8 + sx] as u32;
+                }
+            }
+            out[y * stride + x] = (sum / area) as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::vec::Vec;
+
+    const RGB_444: &[u8] = include_bytes!("../testdata/jpeg/rgb444.jpg");
+    const RGB_420: &[u8] = include_bytes!("../testdata/jpeg/rgb420.jpg");
+    const RESTART: &[u8] = include_bytes!("../testdata/jpeg/restart.jpg");
+    const GREY: &[u8] = include_bytes!("../testdata/jpeg/grey.jpg");
+    const PROGRESSIVE: &[u8] = include_bytes!("../testdata/jpeg/progressive.jpg");
+
+    /// Colours of the top left, top right, bottom left and bottom right quarters of the colour
+    /// fixtures
+    const QUARTERS: [[u8; 3]; 4] = [[200, 40, 40], [40, 200, 40], [40, 40, 200], [230, 230, 230]];
+
+    /// Decode the rows and columns of `image` in the given ranges, checking the strips arrive
+    /// in order
+    fn decode_area(
+        image: &JpegImage,
+        scale: Scale,
+        columns: (u32, u32),
+        rows: (u32, u32),
+    ) -> Result<Vec<u16>, JpegError> {
+        let mut pixels = Vec::new();
+        let mut next = rows.0;
+        image.decode(scale, columns, rows, |first, count, strip| {
+            assert_eq!(first, next);
+            assert_eq!(strip.len(), (count * (columns.1 - columns.0)) as usize);
+            next += count;
+            pixels.extend_from_slice(strip);
+            Ok(())
+        })?;
+        assert_eq!(next, rows.1);
+        Ok(pixels)
+    }
+
+    fn decode(data: &[u8], scale: Scale) -> Result<(Vec<u16>, u32), JpegError> {
+        let image = JpegImage::new(data)?;
+        let (width, height) = image.scaled_size(scale);
+        let pixels = decode_area(&image, scale, (0, width), (0, height))?;
+        Ok((pixels, width))
+    }
+
+    /// Check a pixel is close to an 8 bit colour, allowing for the loss of the encoding
+    fn assert_close(pixel: u16, [r, g, b]: [u8; 3]) {
+        let channels = [
+            (pixel >> 11) as i32,
+            (pixel >> 5 & 0x3F) as i32,
+            (pixel & 0x1F) as i32,
+        ];
+        let expected = [r as i32 >> 3, g as i32 >> 2, b as i32 >> 3];
+        let tolerance = [2, 4, 2];
+        for i in 0..3 {
+            assert!(
+                (channels[i] - expected[i]).abs() <= tolerance[i],
+                "{:04x} is not close to {:?}",
+                pixel,
+                [r, g, b]
+            );
+        }
+    }
+
+    /// Check the colour of the middle of every quarter of a decoded colour fixture
+    fn assert_quarters(pixels: &[u16], width: u32, height: u32) {
+        for (i, &color) in QUARTERS.iter().enumerate() {
+            let x = (i as u32 % 2) * width / 2 + width / 4;
+            let y = (i as u32 / 2) * height / 2 + height / 4;
+            assert_close(pixels[(y * width + x) as usize], color);
+        }
+    }
+
+    #[test]
+    fn full_resolution_chroma() {
+        let image = JpegImage::new(RGB_444).unwrap();
+        assert_eq!((image.width(), image.height()), (32, 32));
+        assert!(image.components[..image.count]
+            .iter()
+            .all(|c| (c.h, c.v) == (1, 1)));
+        let (pixels, width) = decode(RGB_444, Scale::Full).unwrap();
+        assert_quarters(&pixels, width, 32);
+    }
+
+    #[test]
+    fn subsampled_chroma() {
+        let image = JpegImage::new(RGB_420).unwrap();
+        // 36x20 pixels is not a whole number of 16x16 MCUs
+        assert_eq!((image.width(), image.height()), (36, 20));
+        assert_eq!((image.components[0].h, image.components[0].v), (2, 2));
+        let mut strips = Vec::new();
+        image
+            .decode(Scale::Full, (0, 36), (0, 20), |first, count, _| {
+                strips.push((first, count));
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(strips, [(0, 16), (16, 4)]);
+        let (pixels, width) = decode(RGB_420, Scale::Full).unwrap();
+        assert_eq!(pixels.len(), 36 * 20);
+        assert_quarters(&pixels, width, 20);
+    }
+
+    #[test]
+    fn greyscale() {
+        let image = JpegImage::new(GREY).unwrap();
+        assert_eq!(image.count, 1);
+        let (pixels, width) = decode(GREY, Scale::Full).unwrap();
+        assert_eq!((width, pixels.len()), (20, 20 * 12));
+        let row = &pixels[6 * 20..7 * 20];
+        for (x, &pixel) in row.iter().enumerate() {
+            let value = (x * 12 + 10) as u8;
+            assert_close(pixel, [value; 3]);
+            // Grey maps to equal red and blue, with green at twice their resolution
+            assert_eq!(pixel >> 11, pixel & 0x1F);
+        }
+        assert!(row.windows(2).all(|pair| pair[0] >> 11 <= pair[1] >> 11));
+    }
+
+    #[test]
+    fn restart_intervals() {
+        let image = JpegImage::new(RESTART).unwrap();
+        assert_eq!(image.restart_interval, 1);
+        // The same image without restart markers decodes to the same pixels
+        assert_eq!(
+            decode(RESTART, Scale::Full).unwrap(),
+            decode(RGB_420, Scale::Full).unwrap()
+        );
+        let image = JpegImage::new(RESTART).unwrap();
+        assert_eq!(
+            decode_area(&image, Scale::Half, (3, 15), (2, 9)).unwrap(),
+            decode_area(
+                &JpegImage::new(RGB_420).unwrap(),
+                Scale::Half,
+                (3, 15),
+                (2, 9)
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn every_scale() {
+        let image = JpegImage::new(RGB_444).unwrap();
+        for (scale, size) in [
+            (Scale::Full, 32),
+            (Scale::Half, 16),
+            (Scale::Quarter, 8),
+            (Scale::Eighth, 4),
+        ] {
+            assert_eq!(image.scaled_size(scale), (size, size));
+            let (pixels, width) = decode(RGB_444, scale).unwrap();
+            assert_eq!((width, pixels.len()), (size, (size * size) as usize));
+            assert_quarters(&pixels, size, size);
+        }
+        assert_eq!(
+            JpegImage::new(RGB_420).unwrap().scaled_size(Scale::Eighth),
+            (5, 3)
+        );
+        assert_eq!(image.scale_to_fit(10, 10), Some(Scale::Quarter));
+        assert_eq!(image.scale_to_fit(3, 3), None);
+    }
+
+    #[test]
+    fn cropping() {
+        let image = JpegImage::new(RGB_420).unwrap();
+        let (full, width) = decode(RGB_420, Scale::Full).unwrap();
+        let (columns, rows) = ((5, 30), (3, 19));
+        let cropped = decode_area(&image, Scale::Full, columns, rows).unwrap();
+        let expected: Vec<u16> = (rows.0..rows.1)
+            .flat_map(|y| (columns.0..columns.1).map(move |x| (x, y)))
+            .map(|(x, y)| full[(y * width + x) as usize])
+            .collect();
+        assert_eq!(cropped, expected);
+    }
+
+    #[test]
+    fn truncated() {
+        for len in 0..RGB_420.len() {
+            let result = decode(&RGB_420[..len], Scale::Full);
+            assert!(
+                matches!(result, Err(JpegError::Malformed)),
+                "{} of {} bytes decoded",
+                len,
+                RGB_420.len()
+            );
+        }
+        assert!(matches!(
+            JpegImage::new(b"GIF89a"),
+            Err(JpegError::Malformed)
+        ));
+    }
+
+    #[test]
+    fn progressive() {
+        assert!(matches!(
+            JpegImage::new(PROGRESSIVE),
+            Err(JpegError::Unsupported)
+        ));
+    }
+}
@@ -4,8 +4,25 @@
 extern crate embedded_hal as hal;
 
 pub mod builder;
+pub mod burnin;
 pub mod command;
 pub mod display;
+pub mod dither;
+pub mod error;
+pub mod fade;
+pub mod image;
+pub mod interface;
+#[cfg(feature = "jpeg")]
+pub mod jpeg;
+#[cfg(test)]
+mod mock;
 pub mod mode;
 pub mod prelude;
 pub mod properties;
+pub mod qoi;
+pub mod rle;
+pub mod sprite;
+pub mod stream;
+
+#[cfg(feature = "macros")]
+pub use ssd1351_macros::include_image;
@@ -0,0 +1,242 @@
+//! A mock display interface for the unit tests, emulating the GDDRAM of the controller
+
+extern crate std;
+
+use std::vec;
+use std::vec::Vec;
+
+use crate::builder::Builder;
+use crate::interface::ReadDataCommand;
+use crate::mode::displaymode::DisplayModeTrait;
+use crate::mode::graphics::GraphicsMode;
+use crate::properties::{DisplayRotation, DisplaySize};
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+
+const SET_COLUMN: u8 = 0x15;
+const SET_ROW: u8 = 0x75;
+const WRITE_RAM: u8 = 0x5C;
+const READ_RAM: u8 = 0x5D;
+const REMAP: u8 = 0xA0;
+
+/// Flatten data sent to an interface into bytes, 16 bit words as big endian
+pub(crate) fn bytes(data: DataFormat<'_>) -> Vec<u8> {
+    match data {
+        DataFormat::U8(bytes) => bytes.to_vec(),
+        DataFormat::U16(words) => words.iter().flat_map(|word| word.to_be_bytes()).collect(),
+        DataFormat::U16BE(words) => words.iter().flat_map(|word| word.to_be_bytes()).collect(),
+        DataFormat::U16LE(words) => words.iter().flat_map(|word| word.to_le_bytes()).collect(),
+        DataFormat::U8Iter(iter) => iter.collect(),
+        DataFormat::U16BEIter(iter) => iter.flat_map(|word| word.to_be_bytes()).collect(),
+        DataFormat::U16LEIter(iter) => iter.flat_map(|word| word.to_le_bytes()).collect(),
+        _ => panic!("unsupported data format"),
+    }
+}
+
+/// Interface which records the commands sent and writes pixels into a 128x128 GDDRAM like the
+/// controller, following the column and row windows and the address increment. Pixels are read
+/// back after a dummy word, a byte per word or with a 16 bit bus a pixel per word, with junk
+/// in the bits above the bus width.
+pub(crate) struct Mock {
+    /// Every command with its arguments, in order. Pixels written to GDDRAM are not arguments.
+    pub commands: Vec<(u8, Vec<u8>)>,
+    /// The GDDRAM, row by row
+    pub ram: Vec<u16>,
+    /// Number of pixels written
+    pub pixels: usize,
+    /// Fail the transfer after this many more with `DisplayError::BusWriteError`
+    pub fail_after: Option<usize>,
+    /// Whether the address increment is column major
+    pub vertical: bool,
+    /// Whether reads return a pixel per word, as on a 16 bit bus
+    pub wide_bus: bool,
+    /// Number of words read since the last command
+    reads: usize,
+    columns: (u8, u8),
+    rows: (u8, u8),
+    address: (u8, u8),
+    command: Option<u8>,
+    high: Option<u8>,
+}
+
+impl Default for Mock {
+    fn default() -> Self {
+        Mock {
+            commands: Vec::new(),
+            ram: vec![0; 128 * 128],
+            pixels: 0,
+            fail_after: None,
+            vertical: false,
+            wide_bus: false,
+            reads: 0,
+            columns: (0, 127),
+            rows: (0, 127),
+            address: (0, 0),
+            command: None,
+            high: None,
+        }
+    }
+}
+
+impl Mock {
+    /// Get a pixel of the GDDRAM
+    pub fn get(&self, column: u8, row: u8) -> u16 {
+        self.ram[row as usize * 128 + column as usize]
+    }
+
+    /// Get the codes of the commands sent, in order
+    pub fn codes(&self) -> Vec<u8> {
+        self.commands.iter().map(|(command, _)| *command).collect()
+    }
+
+    fn write(&mut self, color: u16) {
+        let (column, row) = self.address;
+        self.ram[row as usize * 128 + column as usize] = color;
+        self.pixels += 1;
+        self.advance();
+    }
+
+    fn read(&mut self) -> u16 {
+        const JUNK: u16 = 0xA500;
+        let reads = self.reads;
+        self.reads += 1;
+        if reads == 0 {
+            return JUNK | 0x5A;
+        }
+        let (column, row) = self.address;
+        let color = self.get(column, row);
+        if self.wide_bus {
+            self.advance();
+            color
+        } else if reads & 1 != 0 {
+            JUNK | color >> 8
+        } else {
+            self.advance();
+            JUNK | color & 0xFF
+        }
+    }
+
+    fn advance(&mut self) {
+        let (column, row) = self.address;
+        let next = |value: u8, (start, end): (u8, u8)| {
+            if value >= end {
+                (start, true)
+            } else {
+                (value + 1, false)
+            }
+        };
+        self.address = if self.vertical {
+            match next(row, self.rows) {
+                (row, true) => (next(column, self.columns).0, row),
+                (row, false) => (column, row),
+            }
+        } else {
+            match next(column, self.columns) {
+                (column, true) => (column, next(row, self.rows).0),
+                (column, false) => (column, row),
+            }
+        };
+    }
+
+    fn check(&mut self) -> Result<(), DisplayError> {
+        match self.fail_after {
+            Some(0) => {
+                self.fail_after = None;
+                Err(DisplayError::BusWriteError)
+            }
+            Some(transfers) => {
+                self.fail_after = Some(transfers - 1);
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl WriteOnlyDataCommand for Mock {
+    fn send_commands(&mut self, commands: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.check()?;
+        for command in bytes(commands) {
+            self.commands.push((command, Vec::new()));
+            self.command = Some(command);
+            self.high = None;
+            self.reads = 0;
+        }
+        Ok(())
+    }
+
+    fn send_data(&mut self, data: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.check()?;
+        for byte in bytes(data) {
+            match self.command {
+                Some(WRITE_RAM) => match self.high.take() {
+                    Some(high) => self.write(u16::from_be_bytes([high, byte])),
+                    None => self.high = Some(byte),
+                },
+                Some(command) => {
+                    let (_, args) = self.commands.last_mut().unwrap();
+                    args.push(byte);
+                    match (command, args.as_slice()) {
+                        (SET_COLUMN, &[start, end]) => {
+                            self.columns = (start, end);
+                            self.address.0 = start;
+                        }
+                        (SET_ROW, &[start, end]) => {
+                            self.rows = (start, end);
+                            self.address.1 = start;
+                        }
+                        (REMAP, &[remap]) => self.vertical = remap & 1 != 0,
+                        _ => {}
+                    }
+                }
+                None => panic!("data sent without a command"),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ReadDataCommand for Mock {
+    fn read_data(&mut self, buf: &mut [u16]) -> Result<(), DisplayError> {
+        self.check()?;
+        assert_eq!(
+            self.command,
+            Some(READ_RAM),
+            "data read without a read command"
+        );
+        for word in buf.iter_mut() {
+            *word = self.read();
+        }
+        Ok(())
+    }
+}
+
+/// Build a graphics mode on a mock, with a framebuffer in buffered mode
+pub(crate) fn graphics(size: DisplaySize, rotation: DisplayRotation) -> GraphicsMode<Mock> {
+    graphics_on(Mock::default(), size, rotation)
+}
+
+/// Build a graphics mode on the given mock, with a framebuffer in buffered mode
+pub(crate) fn graphics_on(
+    mock: Mock,
+    size: DisplaySize,
+    rotation: DisplayRotation,
+) -> GraphicsMode<Mock> {
+    let builder = Builder::new().with_size(size).with_rotation(rotation);
+    #[cfg(not(feature = "buffered"))]
+    let mode = builder.connect_interface(mock);
+    #[cfg(feature = "buffered")]
+    let mode = {
+        let buffer = vec![0; size.num_pixels() * 2].leak();
+        builder.connect_interface(mock, buffer)
+    };
+    mode.into()
+}
+
+/// Release the mock of a graphics mode
+pub(crate) fn release(mode: GraphicsMode<Mock>) -> Mock {
+    #[cfg(not(feature = "buffered"))]
+    let display = mode.release();
+    #[cfg(feature = "buffered")]
+    let (display, _) = mode.release();
+    display.release()
+}
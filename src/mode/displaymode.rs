@@ -1,6 +1,7 @@
 //! Abstraction of different operating modes for the SSD1351
 
 use crate::display::Display;
+use crate::interface::NoResetPin;
 use display_interface::WriteOnlyDataCommand;
 
 /// Display abstraction
@@ -9,29 +10,29 @@ pub struct DisplayMode<MODE> {
 }
 
 /// Trait with core functionality for display switching
-pub trait DisplayModeTrait<DI> {
+pub trait DisplayModeTrait<DI, RST = NoResetPin> {
     /// Allocate all required data and initialise display for display
     #[cfg(not(feature = "buffered"))]
-    fn new(display: Display<DI>) -> Self;
+    fn new(display: Display<DI, RST>) -> Self;
 
     #[cfg(feature = "buffered")]
-    fn new(display: Display<DI>, buffer: &'static mut [u8]) -> Self;
+    fn new(display: Display<DI, RST>, buffer: &'static mut [u8]) -> Self;
 
     /// Release resources for reuse with different display
     #[cfg(not(feature = "buffered"))]
-    fn release(self) -> Display<DI>;
+    fn release(self) -> Display<DI, RST>;
 
     #[cfg(feature = "buffered")]
-    fn release(self) -> (Display<DI>, &'static mut [u8]);
+    fn release(self) -> (Display<DI, RST>, &'static mut [u8]);
 }
 
 impl<MODE> DisplayMode<MODE> {
     /// Setup display to run in requested display
     #[cfg(not(feature = "buffered"))]
-    pub fn new<DI>(display: Display<DI>) -> Self
+    pub fn new<DI, RST>(display: Display<DI, RST>) -> Self
     where
         DI: WriteOnlyDataCommand,
-        MODE: DisplayModeTrait<DI>,
+        MODE: DisplayModeTrait<DI, RST>,
     {
         DisplayMode {
             display: MODE::new(display),
@@ -39,10 +40,10 @@ impl<MODE> DisplayMode<MODE> {
     }
 
     #[cfg(feature = "buffered")]
-    pub fn new<DI>(display: Display<DI>, buffer: &'static mut [u8]) -> Self
+    pub fn new<DI, RST>(display: Display<DI, RST>, buffer: &'static mut [u8]) -> Self
     where
         DI: WriteOnlyDataCommand,
-        MODE: DisplayModeTrait<DI>,
+        MODE: DisplayModeTrait<DI, RST>,
     {
         DisplayMode {
             display: MODE::new(display, buffer),
@@ -52,20 +53,20 @@ impl<MODE> DisplayMode<MODE> {
     /// Change into any display implementing DisplayModeTrait
     // TODO: Figure out how to stay as generic DisplayMode but act as particular display
     #[cfg(not(feature = "buffered"))]
-    pub fn into<DI, NMODE: DisplayModeTrait<DI>>(self) -> NMODE
+    pub fn into<DI, RST, NMODE: DisplayModeTrait<DI, RST>>(self) -> NMODE
     where
         DI: WriteOnlyDataCommand,
-        MODE: DisplayModeTrait<DI>,
+        MODE: DisplayModeTrait<DI, RST>,
     {
         let display = self.display.release();
         NMODE::new(display)
     }
 
     #[cfg(feature = "buffered")]
-    pub fn into<DI, NMODE: DisplayModeTrait<DI>>(self) -> NMODE
+    pub fn into<DI, RST, NMODE: DisplayModeTrait<DI, RST>>(self) -> NMODE
     where
         DI: WriteOnlyDataCommand,
-        MODE: DisplayModeTrait<DI>,
+        MODE: DisplayModeTrait<DI, RST>,
     {
         let (display, buffer) = self.display.release();
         NMODE::new(display, buffer)
@@ -1,64 +1,97 @@
+use crate::burnin::{PixelShift, Screensaver, ScreensaverAction};
 use crate::display::Display;
-use display_interface::{DisplayError, WriteOnlyDataCommand};
+#[cfg(feature = "buffered")]
+use crate::dither;
+use crate::dither::{to_rgb565, Dithering};
+use crate::error::Error;
+use crate::fade::Fade;
+use crate::image::Image;
+use crate::interface::NoResetPin;
+use crate::interface::ReadDataCommand;
+#[cfg(feature = "jpeg")]
+use crate::jpeg::{JpegError, JpegImage, Scale};
+use crate::qoi::{QoiError, QoiImage};
+use crate::rle::RleImage;
+use crate::sprite::Sprite;
+use display_interface::WriteOnlyDataCommand;
 use hal::delay::DelayNs;
 use hal::digital::OutputPin;
 
 use crate::mode::displaymode::DisplayModeTrait;
-use crate::properties::DisplayRotation;
+use crate::properties::{
+    DisplayRotation, GpioMode, PhaseLength, PrechargeVoltage, SecondPrechargePeriod, Vsl,
+};
 
 /// Graphics Mode for the display
-pub struct GraphicsMode<DI>
+pub struct GraphicsMode<DI, RST = NoResetPin>
 where
     DI: WriteOnlyDataCommand,
 {
-    display: Display<DI>,
+    display: Display<DI, RST>,
     #[cfg(feature = "buffered")]
     pub buffer: &'static mut [u8],
+    #[cfg(feature = "buffered")]
+    pub(crate) offset: (i8, i8),
 }
 
-impl<DI> DisplayModeTrait<DI> for GraphicsMode<DI>
+/// The on screen part of a block of pixels, in rotated coordinates, and the offset of its first
+/// pixel into the block
+struct Clip {
+    x: u8,
+    y: u8,
+    width: u8,
+    height: u8,
+    skip_x: usize,
+    skip_y: usize,
+}
+
+impl<DI, RST> DisplayModeTrait<DI, RST> for GraphicsMode<DI, RST>
 where
     DI: WriteOnlyDataCommand,
 {
     #[cfg(not(feature = "buffered"))]
     /// Create new GraphicsMode instance
-    fn new(display: Display<DI>) -> Self {
+    fn new(display: Display<DI, RST>) -> Self {
         GraphicsMode { display }
     }
 
     #[cfg(feature = "buffered")]
-    fn new(display: Display<DI>, buffer: &'static mut [u8]) -> Self {
-        GraphicsMode { display, buffer }
+    fn new(display: Display<DI, RST>, buffer: &'static mut [u8]) -> Self {
+        GraphicsMode {
+            display,
+            buffer,
+            offset: (0, 0),
+        }
     }
 
     #[cfg(not(feature = "buffered"))]
     /// Release all resources used by GraphicsMode
-    fn release(self) -> Display<DI> {
+    fn release(self) -> Display<DI, RST> {
         self.display
     }
 
     #[cfg(feature = "buffered")]
     /// Release all resources used by GraphicsMode
-    fn release(self) -> (Display<DI>, &'static mut [u8]) {
+    fn release(self) -> (Display<DI, RST>, &'static mut [u8]) {
         (self.display, self.buffer)
     }
 }
 
 // impl<DI: DisplayInterface> GraphicsMode<DI> {
 //     /// Create a new grahpics display interface
-//     pub fn new(display: Display<DI>) -> Self {
+//     pub fn new(display: Display<DI, RST>) -> Self {
 //         GraphicsMode { display }
 //     }
 // }
 
-impl<DI> GraphicsMode<DI>
+impl<DI, RST> GraphicsMode<DI, RST>
 where
     DI: WriteOnlyDataCommand,
 {
     #[cfg(not(feature = "buffered"))]
     /// Clear the display
-    pub fn clear(&mut self) {
-        self.display.clear().unwrap();
+    pub fn clear(&mut self) -> Result<(), Error> {
+        self.display.clear()
     }
 
     #[cfg(feature = "buffered")]
@@ -72,17 +105,35 @@ where
         }
     }
 
-    /// Reset display
-    pub fn reset<RST, DELAY>(&mut self, rst: &mut RST, delay: &mut DELAY) -> Result<(), RST::Error>
+    /// Reset display using the given pin and the reset timing set on the builder. Displays built
+    /// with [`Builder::connect_interface_with_reset`](crate::builder::Builder::connect_interface_with_reset) are reset and
+    /// initialised already and can use `reinit` instead.
+    pub fn reset<PIN, DELAY>(&mut self, rst: &mut PIN, delay: &mut DELAY) -> Result<(), PIN::Error>
     where
-        RST: OutputPin,
+        PIN: OutputPin,
         DELAY: DelayNs,
     {
+        let timing = self.display.get_reset_timing();
+        self.display.forget_address();
         rst.set_high()?;
-        delay.delay_ms(1);
+        delay.delay_us(timing.before_us);
         rst.set_low()?;
-        delay.delay_ms(10);
+        delay.delay_us(timing.low_us);
         rst.set_high()?;
+        delay.delay_us(timing.after_us);
+        Ok(())
+    }
+
+    /// Reset the display with the reset pin given to the builder and initialise it again with the
+    /// current settings, e.g. to recover from a brown out or a corrupted controller state
+    pub fn reinit<DELAY>(&mut self, delay: &mut DELAY) -> Result<(), Error>
+    where
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        self.display.reinit(delay)?;
+        #[cfg(feature = "buffered")]
+        self.flush();
         Ok(())
     }
 
@@ -101,48 +152,608 @@ where
     #[cfg(not(feature = "buffered"))]
     /// Turn a pixel on or off. A non-zero `value` is treated as on, `0` as off. If the X and Y
     /// coordinates are out of the bounds of the display, this method call is a noop.
-    pub fn set_pixel(&mut self, x: u32, y: u32, color: u16) {
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: u16) -> Result<(), Error> {
         let (display_width, display_height) = self.display.get_size().dimensions();
         let rot = self.display.get_rotation();
         let (nx, ny) = match rot {
             DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (x, y),
             DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (y, x),
         };
+        if nx >= display_width as u32 || ny >= display_height as u32 {
+            return Ok(());
+        }
         self.display
-            .set_draw_area((nx as u8, ny as u8), (display_width, display_height))
-            .unwrap();
-        self.display
-            .draw(&[(color >> 8) as u8, color as u8])
-            .unwrap();
+            .set_draw_area((nx as u8, ny as u8), (display_width, display_height))?;
+        self.display.draw(&[(color >> 8) as u8, color as u8])
     }
 
     #[cfg(feature = "buffered")]
     /// Turn a pixel on or off. A non-zero `value` is treated as on, `0` as off. If the X and Y
     /// coordinates are out of the bounds of the display, this method call is a noop.
     pub fn set_pixel(&mut self, x: u32, y: u32, color: u16) {
-        // set bytes in buffer
-        self.buffer[(y as usize * 128usize + x as usize) * 2] = (color >> 8) as u8;
-        self.buffer[((y as usize * 128usize + x as usize) * 2) + 1usize] = color as u8;
+        let (width, height) = self.display.get_dimensions();
+        if x >= width as u32 || y >= height as u32 {
+            return;
+        }
+        // set bytes in buffer, rows are as wide as the rotated display
+        let i = (y as usize * width as usize + x as usize) * 2;
+        self.buffer[i] = (color >> 8) as u8;
+        self.buffer[i + 1] = color as u8;
+    }
+
+    /// Copy a `width` by `height` block of big endian Rgb565 pixels, row by row, to `x`, `y`.
+    /// The block is clipped to the screen, the visible part is sent through a single window.
+    /// Returns `Error::OutOfRange` if `data` is shorter than the block.
+    pub fn blit(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        let stride = width as usize * 2;
+        if data.len() < stride * height as usize {
+            return Err(Error::OutOfRange);
+        }
+        let clip = match self.clip(x, y, width, height) {
+            Some(clip) => clip,
+            None => return Ok(()),
+        };
+        let row_len = clip.width as usize * 2;
+        let rows = data[clip.skip_y * stride..]
+            .chunks(stride)
+            .take(clip.height as usize)
+            .map(|row| &row[clip.skip_x * 2..clip.skip_x * 2 + row_len]);
+
+        #[cfg(not(feature = "buffered"))]
+        {
+            self.set_window(clip.x, clip.y, clip.width, clip.height)?;
+            if clip.width as u32 == width {
+                // Visible rows are contiguous in the source
+                let start = clip.skip_y * stride;
+                self.display
+                    .draw(&data[start..start + row_len * clip.height as usize])?;
+            } else {
+                for row in rows {
+                    self.display.draw(row)?;
+                }
+            }
+        }
+
+        #[cfg(feature = "buffered")]
+        {
+            let fb_stride = self.display.get_dimensions().0 as usize * 2;
+            for (i, row) in rows.enumerate() {
+                let start = (clip.y as usize + i) * fb_stride + clip.x as usize * 2;
+                self.buffer[start..start + row_len].copy_from_slice(row);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Copy a `width` by `height` block of Rgb565 pixels, row by row, to `x`, `y`, like `blit`
+    pub fn blit_pixels(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        pixels: &[u16],
+    ) -> Result<(), Error> {
+        let stride = width as usize;
+        if pixels.len() < stride * height as usize {
+            return Err(Error::OutOfRange);
+        }
+        let clip = match self.clip(x, y, width, height) {
+            Some(clip) => clip,
+            None => return Ok(()),
+        };
+        let row_len = clip.width as usize;
+        let rows = pixels[clip.skip_y * stride..]
+            .chunks(stride)
+            .take(clip.height as usize)
+            .map(|row| &row[clip.skip_x..clip.skip_x + row_len]);
+
+        #[cfg(not(feature = "buffered"))]
+        {
+            self.set_window(clip.x, clip.y, clip.width, clip.height)?;
+            if clip.width as u32 == width {
+                // Visible rows are contiguous in the source
+                let start = clip.skip_y * stride;
+                self.display
+                    .draw_pixels(&pixels[start..start + row_len * clip.height as usize])?;
+            } else {
+                for row in rows {
+                    self.display.draw_pixels(row)?;
+                }
+            }
+        }
+
+        #[cfg(feature = "buffered")]
+        {
+            let fb_stride = self.display.get_dimensions().0 as usize * 2;
+            for (i, row) in rows.enumerate() {
+                let start = (clip.y as usize + i) * fb_stride + clip.x as usize * 2;
+                let dest = &mut self.buffer[start..start + row_len * 2];
+                for (bytes, pixel) in dest.chunks_exact_mut(2).zip(row) {
+                    bytes.copy_from_slice(&pixel.to_be_bytes());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Draw an image at `x`, `y`, clipped to the screen, like `blit`
+    pub fn draw_image(&mut self, x: i32, y: i32, image: &Image) -> Result<(), Error> {
+        self.blit(x, y, image.width(), image.height(), image.data())
+    }
+
+    /// Draw a run length encoded image at `x`, `y`, clipped to the screen. The visible part is
+    /// decoded straight into a single window, every run becoming one repeated colour transfer,
+    /// or is written into the framebuffer in buffered mode.
+    pub fn draw_rle_image(&mut self, x: i32, y: i32, image: &RleImage) -> Result<(), Error> {
+        let clip = match self.clip(x, y, image.width(), image.height()) {
+            Some(clip) => clip,
+            None => return Ok(()),
+        };
+        let width = image.width() as usize;
+        let full_rows = clip.width as usize == width;
+        let (first, last) = (clip.skip_x, clip.skip_x + clip.width as usize);
+        let visible = clip.skip_y * width..(clip.skip_y + clip.height as usize) * width;
+
+        #[cfg(not(feature = "buffered"))]
+        self.set_window(clip.x, clip.y, clip.width, clip.height)?;
+
+        let mut start = 0;
+        for (count, color) in image.runs() {
+            let end = start + count as usize;
+            let stop = end.min(visible.end);
+            let mut pos = start.max(visible.start);
+            while pos < stop {
+                // Visible pixels of the run, within one row unless whole rows are visible
+                let row = pos / width * width;
+                let (from, to, next) = if full_rows {
+                    (pos, stop, stop)
+                } else {
+                    (pos.max(row + first), stop.min(row + last), row + width)
+                };
+                if from < to {
+                    #[cfg(not(feature = "buffered"))]
+                    self.display.draw_repeated(color, to - from)?;
+
+                    #[cfg(feature = "buffered")]
+                    for index in from..to {
+                        let px = clip.x as usize + index % width - first;
+                        let py = clip.y as usize + index / width - clip.skip_y;
+                        self.set_pixel(px as u32, py as u32, color);
+                    }
+                }
+                pos = next;
+            }
+            start = end;
+        }
+
+        Ok(())
+    }
+
+    /// Decode a QOI image to `x`, `y`, clipped to the screen. The visible rows are converted
+    /// to Rgb565 with `dithering` in a line buffer and each is sent with `draw` into a single
+    /// window, or copied into the framebuffer in buffered mode. Alpha is ignored.
+    pub fn draw_qoi(
+        &mut self,
+        x: i32,
+        y: i32,
+        image: &QoiImage,
+        dithering: Dithering,
+    ) -> Result<(), QoiError> {
+        let clip = match self.clip(x, y, image.width(), image.height()) {
+            Some(clip) => clip,
+            None => return Ok(()),
+        };
+        let width = image.width() as usize;
+        let row_len = clip.width as usize * 2;
+        let mut pixels = image.pixels();
+        let mut line = [0u8; 256];
+
+        #[cfg(not(feature = "buffered"))]
+        self.set_window(clip.x, clip.y, clip.width, clip.height)?;
+
+        // Skip the rows above the screen, the rows below are never decoded
+        let skip = clip.skip_y * width;
+        if pixels.by_ref().take(skip).count() < skip {
+            return Err(QoiError::Truncated);
+        }
+        for row in 0..clip.height {
+            let py = clip.y as i32 + row as i32;
+            let mut source = pixels.by_ref().take(width).skip(clip.skip_x);
+            for (col, bytes) in line[..row_len].chunks_exact_mut(2).enumerate() {
+                let [r, g, b, _] = source.next().ok_or(QoiError::Truncated)?;
+                let color = to_rgb565([r, g, b], clip.x as i32 + col as i32, py, dithering);
+                bytes.copy_from_slice(&color.to_be_bytes());
+            }
+            // Decode the rest of the row
+            if source.count() < width - clip.skip_x - clip.width as usize {
+                return Err(QoiError::Truncated);
+            }
+
+            #[cfg(not(feature = "buffered"))]
+            self.display.draw(&line[..row_len])?;
+
+            #[cfg(feature = "buffered")]
+            {
+                let fb_stride = self.display.get_dimensions().0 as usize * 2;
+                let start = py as usize * fb_stride + clip.x as usize * 2;
+                self.buffer[start..start + row_len].copy_from_slice(&line[..row_len]);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "jpeg")]
+    /// Decode a JPEG image scaled down by `scale` to `x`, `y`, clipped to the screen. The
+    /// visible part of every row of MCUs is sent into a single window as soon as it is decoded,
+    /// or copied into the framebuffer in buffered mode.
+    pub fn draw_jpeg(
+        &mut self,
+        x: i32,
+        y: i32,
+        image: &JpegImage,
+        scale: Scale,
+    ) -> Result<(), JpegError> {
+        let (width, height) = image.scaled_size(scale);
+        let clip = match self.clip(x, y, width, height) {
+            Some(clip) => clip,
+            None => return Ok(()),
+        };
+        let columns = (clip.skip_x as u32, clip.skip_x as u32 + clip.width as u32);
+        let rows = (clip.skip_y as u32, clip.skip_y as u32 + clip.height as u32);
+
+        #[cfg(not(feature = "buffered"))]
+        self.set_window(clip.x, clip.y, clip.width, clip.height)?;
+
+        #[cfg(feature = "buffered")]
+        let mut top = clip.y as usize;
+
+        image.decode(scale, columns, rows, |_, _, pixels| {
+            #[cfg(not(feature = "buffered"))]
+            self.display.draw_pixels(pixels)?;
+
+            #[cfg(feature = "buffered")]
+            {
+                let fb_stride = self.display.get_dimensions().0 as usize * 2;
+                for line in pixels.chunks(clip.width as usize) {
+                    let start = top * fb_stride + clip.x as usize * 2;
+                    let dest = &mut self.buffer[start..start + line.len() * 2];
+                    for (bytes, pixel) in dest.chunks_exact_mut(2).zip(line) {
+                        bytes.copy_from_slice(&pixel.to_be_bytes());
+                    }
+                    top += 1;
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Draw a sprite at `x`, `y`, leaving the pixels under its key colour untouched. Each row is
+    /// split into runs of opaque pixels which are sent through their own window, or copied into
+    /// the framebuffer in buffered mode. The sprite is clipped to the screen.
+    pub fn draw_sprite(&mut self, x: i32, y: i32, sprite: &Sprite) -> Result<(), Error> {
+        let clip = match self.clip(x, y, sprite.width(), sprite.height()) {
+            Some(clip) => clip,
+            None => return Ok(()),
+        };
+        let start = clip.skip_x as u32;
+        let end = start + clip.width as u32;
+        for row in clip.skip_y as u32..clip.skip_y as u32 + clip.height as u32 {
+            let data = sprite.row(row);
+            for (run_x, len) in sprite.opaque_runs(row, start, end) {
+                let run = &data[run_x as usize * 2..(run_x + len) as usize * 2];
+                self.blit(x + run_x as i32, y + row as i32, len, 1, run)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "buffered")]
+    /// Copy a `width` by `height` image of 8 bit red, green and blue bytes, row by row, to `x`,
+    /// `y` in the framebuffer. The colours are reduced to Rgb565 with Floyd-Steinberg error
+    /// diffusion over the whole image, so it is smoother than ordered dithering but can't be
+    /// drawn in parts. Images are at most 128 pixels wide and clipped to the screen. Returns
+    /// `Error::OutOfRange` if the image is wider or `data` is shorter than the image.
+    pub fn blit_rgb888(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        if data.len() < width as usize * height as usize * 3 {
+            return Err(Error::OutOfRange);
+        }
+        self.blit_diffused(x, y, width, height, |i| {
+            [data[i * 3], data[i * 3 + 1], data[i * 3 + 2]]
+        })
+    }
+
+    #[cfg(feature = "buffered")]
+    /// Copy a `width` by `height` image of 8 bit grey levels to `x`, `y` in the framebuffer with
+    /// error diffusion, like `blit_rgb888`
+    pub fn blit_gray8(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        if data.len() < width as usize * height as usize {
+            return Err(Error::OutOfRange);
+        }
+        self.blit_diffused(x, y, width, height, |i| [data[i]; 3])
+    }
+
+    #[cfg(feature = "buffered")]
+    /// Floyd-Steinberg dither an image, getting the channels of its `i`th pixel from `pixel`
+    fn blit_diffused<F>(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        pixel: F,
+    ) -> Result<(), Error>
+    where
+        F: Fn(usize) -> [u8; 3],
+    {
+        const MAX_WIDTH: usize = 128;
+        const BITS: [u32; 3] = [5, 6, 5];
+
+        let width = width as usize;
+        if width > MAX_WIDTH {
+            return Err(Error::OutOfRange);
+        }
+        // Errors carried to the current and next row in 1/16ths, with a column of padding on
+        // either side
+        let mut current = [[0i16; 3]; MAX_WIDTH + 2];
+        let mut next = [[0i16; 3]; MAX_WIDTH + 2];
+
+        for row in 0..height as usize {
+            for col in 0..width {
+                let rgb = pixel(row * width + col);
+                let mut color = 0u16;
+                for channel in 0..3 {
+                    let bits = BITS[channel];
+                    let value =
+                        (rgb[channel] as i16 + current[col + 1][channel] / 16).clamp(0, 255);
+                    let reduced = (value as u16) >> (8 - bits);
+                    let error = value - dither::expand(reduced, bits) as i16;
+                    current[col + 2][channel] += error * 7;
+                    next[col][channel] += error * 3;
+                    next[col + 1][channel] += error * 5;
+                    next[col + 2][channel] += error;
+                    color = (color << bits) | reduced;
+                }
+
+                let (px, py) = (x + col as i32, y + row as i32);
+                if px >= 0 && py >= 0 {
+                    self.set_pixel(px as u32, py as u32, color);
+                }
+            }
+            current = next;
+            next = [[0i16; 3]; MAX_WIDTH + 2];
+        }
+        Ok(())
+    }
+
+    /// Move the image to the next offset of a burn-in pixel shift once its interval passed at the
+    /// monotonic time `now_ms`. Returns `true` if the image moved.
+    ///
+    /// In buffered mode the framebuffer is flushed shifted by the offset, with the exposed edges
+    /// black. In unbuffered mode there is no frame to shift, so only the rows move, in hardware
+    /// with the display start line, and the column part of the offset is ignored. The start line
+    /// wraps at the 128 rows of GDDRAM, so unbuffered shifts return [`Error::OutOfRange`] on
+    /// 128x96 panels, which would show the unused GDDRAM rows.
+    pub fn update_pixel_shift(
+        &mut self,
+        shift: &mut PixelShift,
+        now_ms: u32,
+    ) -> Result<bool, Error> {
+        #[cfg(not(feature = "buffered"))]
+        if self.display.get_size().dimensions().1 < 128 {
+            return Err(Error::OutOfRange);
+        }
+        if !shift.advance(now_ms) {
+            return Ok(false);
+        }
+        let (columns, rows) = shift.offset();
+
+        #[cfg(feature = "buffered")]
+        {
+            // The offset is in panel columns and rows, the framebuffer is in rotated coordinates
+            self.offset = match self.display.get_rotation() {
+                DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (columns, rows),
+                DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (rows, columns),
+            };
+            self.flush();
+        }
+        #[cfg(not(feature = "buffered"))]
+        {
+            let _ = columns;
+            self.display
+                .set_start_line((rows as i16).rem_euclid(128) as u8)?;
+        }
+
+        Ok(true)
+    }
+
+    /// Blank or dim the display once the screensaver timed out at the monotonic time `now_ms`,
+    /// and restore it if activity was reported since. Returns whether the screensaver is active.
+    pub fn update_screensaver(
+        &mut self,
+        saver: &mut Screensaver,
+        now_ms: u32,
+    ) -> Result<bool, Error> {
+        let woken = saver.take_wake();
+        match saver.saved_brightness() {
+            Some(brightness) if woken => {
+                match saver.action() {
+                    ScreensaverAction::Blank => self.set_display_on(true)?,
+                    ScreensaverAction::Dim(_) => self.set_brightness(brightness)?,
+                }
+                saver.set_active(None);
+            }
+            None if saver.is_idle(now_ms) => {
+                let brightness = self.get_brightness();
+                match saver.action() {
+                    ScreensaverAction::Blank => self.set_display_on(false)?,
+                    ScreensaverAction::Dim(level) => self.set_brightness(level)?,
+                }
+                saver.set_active(Some(brightness));
+            }
+            _ => {}
+        }
+        Ok(saver.is_active())
+    }
+
+    /// Draw a vertical run of Rgb565 pixels down from `x`, `y`, clipped to the screen. Rather
+    /// than switching the address increment to column major, the run is sent through a window one
+    /// pixel wide, in which the row major increment moves down a row after every pixel.
+    pub fn draw_vertical_run(&mut self, x: i32, y: i32, pixels: &[u16]) -> Result<(), Error> {
+        let clip = match self.clip(x, y, 1, pixels.len() as u32) {
+            Some(clip) => clip,
+            None => return Ok(()),
+        };
+        let pixels = &pixels[clip.skip_y..clip.skip_y + clip.height as usize];
+
+        #[cfg(not(feature = "buffered"))]
+        {
+            self.set_window(clip.x, clip.y, 1, clip.height)?;
+            self.display.draw_pixels(pixels)?;
+        }
+
+        #[cfg(feature = "buffered")]
+        for (row, &color) in pixels.iter().enumerate() {
+            self.set_pixel(clip.x as u32, clip.y as u32 + row as u32, color);
+        }
+
+        Ok(())
+    }
+
+    /// Get the part of a `width` by `height` block at `x`, `y` which is on the screen, if any
+    fn clip(&self, x: i32, y: i32, width: u32, height: u32) -> Option<Clip> {
+        let (display_width, display_height) = self.display.get_dimensions();
+        let sx = (x as i64).max(0);
+        let sy = (y as i64).max(0);
+        let ex = (x as i64 + width as i64).min(display_width as i64);
+        let ey = (y as i64 + height as i64).min(display_height as i64);
+        if sx >= ex || sy >= ey {
+            return None;
+        }
+        Some(Clip {
+            x: sx as u8,
+            y: sy as u8,
+            width: (ex - sx) as u8,
+            height: (ey - sy) as u8,
+            skip_x: (sx - x as i64) as usize,
+            skip_y: (sy - y as i64) as usize,
+        })
+    }
+
+    #[cfg(not(feature = "buffered"))]
+    /// Set the hardware window to an on screen area given in rotated coordinates
+    pub(crate) fn set_window(&mut self, x: u8, y: u8, width: u8, height: u8) -> Result<(), Error> {
+        let (ex, ey) = (x + width, y + height);
+        let (area_start, area_end) = match self.display.get_rotation() {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => ((x, y), (ex, ey)),
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => ((y, x), (ey, ex)),
+        };
+        self.display.set_draw_area(area_start, area_end)
+    }
+
+    #[cfg(not(feature = "buffered"))]
+    /// Continue writing row by row into an on screen window given in rotated coordinates, at
+    /// its pixel `cursor`. Nothing is sent while the controller is still writing there, e.g.
+    /// when the previous pixels went into the same window. Otherwise the rest of the row of
+    /// the cursor, or the rows from the cursor down, get a window of their own since the
+    /// controller can only write from the start of one. Returns how many pixels can be sent
+    /// with `write_pixels` before this has to be called again.
+    pub(crate) fn continue_window(
+        &mut self,
+        x: u8,
+        y: u8,
+        width: u8,
+        height: u8,
+        cursor: usize,
+    ) -> Result<usize, Error> {
+        let row_length = width as usize;
+        let (column, row) = ((cursor % row_length) as u8, (cursor / row_length) as u8);
+        let (cx, cy) = (x + column, y + row);
+        let (ex, ey) = (x + width, y + height);
+        let rows_left = (ey - cy) as usize * row_length - column as usize;
+
+        let transposed = matches!(
+            self.display.get_rotation(),
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270
+        );
+        let flip = |(a, b): (u8, u8)| if transposed { (b, a) } else { (a, b) };
+        if let Some([start, end, at]) = self.display.draw_area() {
+            let (start, end, at) = (flip(start), flip(end), flip(at));
+            if at == (cx, cy) && end.0 == ex {
+                if start.0 == x && start.1 == y && end.1 == ey {
+                    // The whole window, which the controller wraps around in by itself
+                    return Ok(usize::MAX);
+                }
+                if start.0 == x && start.1 > y && start.1 <= cy && end.1 == ey {
+                    return Ok(rows_left);
+                }
+                if start.0 <= cx && start.1 == cy && end.1 == cy + 1 {
+                    return Ok((ex - cx) as usize);
+                }
+            }
+        }
+
+        if cursor == 0 {
+            self.set_window(x, y, width, height)?;
+            Ok(usize::MAX)
+        } else if column == 0 {
+            self.set_window(x, cy, width, ey - cy)?;
+            Ok(rows_left)
+        } else {
+            self.set_window(cx, cy, ex - cx, 1)?;
+            Ok((ex - cx) as usize)
+        }
+    }
+
+    #[cfg(not(feature = "buffered"))]
+    /// Send Rgb565 pixels into the window set with `set_window`
+    pub(crate) fn write_pixels(&mut self, pixels: &[u16]) -> Result<(), Error> {
+        if pixels.is_empty() {
+            return Ok(());
+        }
+        self.display.draw_pixels(pixels)
     }
 
     #[cfg(feature = "buffered")]
+    /// Send the framebuffer to the display, shifted by the current burn-in offset if any
     pub fn flush(&mut self) {
-        let (display_width, display_height) = self.display.get_size().dimensions();
-        self.display
-            .set_draw_area((0, 0), (display_width, display_height))
-            .unwrap();
-        self.display.draw(self.buffer).unwrap();
+        self.display.draw_frame(self.buffer, self.offset).unwrap();
     }
 
     /// Display is set up in column mode, i.e. a byte walks down a column of 8 pixels from
     /// column 0 on the left, to column _n_ on the right
-    pub fn init(&mut self) -> Result<(), DisplayError> {
+    pub fn init(&mut self) -> Result<(), Error> {
         self.display.init()?;
         Ok(())
     }
 
     /// Set the display rotation
-    pub fn set_rotation(&mut self, rot: DisplayRotation) -> Result<(), DisplayError> {
+    pub fn set_rotation(&mut self, rot: DisplayRotation) -> Result<(), Error> {
         self.display.set_rotation(rot)
     }
 
@@ -150,24 +761,244 @@ where
     pub fn get_dimensions(&self) -> (u8, u8) {
         self.display.get_dimensions()
     }
+
+    /// Set the mode of the GPIO0 pin
+    pub fn set_gpio0(&mut self, mode: GpioMode) -> Result<(), Error> {
+        self.display.set_gpio0(mode)
+    }
+
+    /// Set the mode of the GPIO1 pin
+    pub fn set_gpio1(&mut self, mode: GpioMode) -> Result<(), Error> {
+        self.display.set_gpio1(mode)
+    }
+
+    /// Lock the MCU interface of the controller, see [`Display::lock_interface`]
+    pub fn lock_interface(&mut self) -> Result<(), Error> {
+        self.display.lock_interface()
+    }
+
+    /// Unlock the MCU interface of the controller
+    pub fn unlock_interface(&mut self) -> Result<(), Error> {
+        self.display.unlock_interface()
+    }
+
+    /// Lock the protected commands, see [`Display::lock_protected_commands`]
+    pub fn lock_protected_commands(&mut self) -> Result<(), Error> {
+        self.display.lock_protected_commands()
+    }
+
+    /// Unlock the protected commands
+    pub fn unlock_protected_commands(&mut self) -> Result<(), Error> {
+        self.display.unlock_protected_commands()
+    }
+
+    /// Set the reset (phase 1) and first precharge (phase 2) periods
+    pub fn set_phase_length(&mut self, phase_length: PhaseLength) -> Result<(), Error> {
+        self.display.set_phase_length(phase_length)
+    }
+
+    /// Set the first precharge voltage
+    pub fn set_precharge_voltage(&mut self, voltage: PrechargeVoltage) -> Result<(), Error> {
+        self.display.set_precharge_voltage(voltage)
+    }
+
+    /// Set the second precharge (phase 3) period
+    pub fn set_second_precharge_period(
+        &mut self,
+        period: SecondPrechargePeriod,
+    ) -> Result<(), Error> {
+        self.display.set_second_precharge_period(period)
+    }
+
+    /// Set the segment low voltage source
+    pub fn set_vsl(&mut self, vsl: Vsl) -> Result<(), Error> {
+        self.display.set_vsl(vsl)
+    }
+
+    /// Enable or disable the display enhancement
+    pub fn set_display_enhancement(&mut self, enable: bool) -> Result<(), Error> {
+        self.display.set_display_enhancement(enable)
+    }
+
+    /// Get the nominal frame rate in Hz
+    pub fn frame_rate(&self) -> u32 {
+        self.display.frame_rate()
+    }
+
+    /// Set the nominal frame rate nearest to `hz`, see [`Display::set_frame_rate`]
+    pub fn set_frame_rate(&mut self, hz: u32) -> Result<(), Error> {
+        self.display.set_frame_rate(hz)
+    }
+
+    /// Get the brightness
+    pub fn get_brightness(&self) -> u8 {
+        self.display.get_brightness()
+    }
+
+    /// Set the brightness from 0 (dimmest) to 255 (brightest), see [`Display::set_brightness`]
+    pub fn set_brightness(&mut self, brightness: u8) -> Result<(), Error> {
+        self.display.set_brightness(brightness)
+    }
+
+    /// Set the contrast of the red, green and blue channels at full brightness
+    pub fn set_channel_contrast(&mut self, red: u8, green: u8, blue: u8) -> Result<(), Error> {
+        self.display.set_channel_contrast(red, green, blue)
+    }
+
+    /// Turn the display panel on or off
+    pub fn set_display_on(&mut self, on: bool) -> Result<(), Error> {
+        self.display.set_display_on(on)
+    }
+
+    /// Create a fade from the current brightness to `brightness` taking `duration_ms`
+    /// milliseconds, to be advanced with `update_fade`
+    pub fn fade(&self, brightness: u8, duration_ms: u32) -> Fade {
+        Fade::new(self.get_brightness(), brightness, duration_ms)
+    }
+
+    /// Advance a fade to `elapsed_ms` milliseconds after its start without blocking. The
+    /// brightness is only sent when it changed. Returns `true` once the fade is done.
+    pub fn update_fade(&mut self, fade: &Fade, elapsed_ms: u32) -> Result<bool, Error> {
+        let brightness = fade.brightness_at(elapsed_ms);
+        if brightness != self.get_brightness() {
+            self.set_brightness(brightness)?;
+        }
+        Ok(fade.is_done(elapsed_ms))
+    }
+
+    /// Ramp the brightness from its current value to `brightness` over `duration_ms`
+    /// milliseconds, blocking until done. Each brightness level in between is sent once.
+    pub fn fade_to<DELAY>(
+        &mut self,
+        brightness: u8,
+        duration_ms: u32,
+        delay: &mut DELAY,
+    ) -> Result<(), Error>
+    where
+        DELAY: DelayNs,
+    {
+        let fade = self.fade(brightness, duration_ms);
+        let steps = fade.steps();
+        if steps == 0 {
+            return self.set_brightness(brightness);
+        }
+
+        let step_us = (duration_ms as u64 * 1000 / steps as u64) as u32;
+        let from = fade.start() as i32;
+        let direction = if brightness as i32 > from { 1 } else { -1 };
+        for step in 1..=steps as i32 {
+            delay.delay_us(step_us);
+            self.set_brightness((from + direction * step) as u8)?;
+        }
+        Ok(())
+    }
+
+    /// Turn the display on at the lowest brightness and ramp it up to `brightness` over
+    /// `duration_ms` milliseconds, e.g. when waking up
+    pub fn fade_in<DELAY>(
+        &mut self,
+        brightness: u8,
+        duration_ms: u32,
+        delay: &mut DELAY,
+    ) -> Result<(), Error>
+    where
+        DELAY: DelayNs,
+    {
+        self.set_brightness(0)?;
+        self.set_display_on(true)?;
+        self.fade_to(brightness, duration_ms, delay)
+    }
+
+    /// Ramp the brightness down to the lowest level over `duration_ms` milliseconds and turn the
+    /// display off, e.g. before going to sleep. The brightness is left at 0, so `fade_in` or
+    /// `set_brightness` should be used when turning it back on.
+    pub fn fade_out<DELAY>(&mut self, duration_ms: u32, delay: &mut DELAY) -> Result<(), Error>
+    where
+        DELAY: DelayNs,
+    {
+        self.fade_to(0, duration_ms, delay)?;
+        self.set_display_on(false)
+    }
+}
+
+impl<DI, RST> GraphicsMode<DI, RST>
+where
+    DI: ReadDataCommand,
+{
+    /// Read back the Rgb565 pixels of a `width` by `height` area of the display RAM starting at
+    /// `x`, `y` into `buf`, row by row in the current rotation. Requires an interface which can
+    /// read from the controller, see [ReadDataCommand].
+    pub fn read_area(
+        &mut self,
+        x: u8,
+        y: u8,
+        width: u8,
+        height: u8,
+        buf: &mut [u16],
+    ) -> Result<(), Error> {
+        let (ex, ey) = match (x.checked_add(width), y.checked_add(height)) {
+            (Some(ex), Some(ey)) => (ex, ey),
+            _ => return Err(Error::OutOfRange),
+        };
+        let (area_start, area_end) = match self.display.get_rotation() {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => ((x, y), (ex, ey)),
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => ((y, x), (ey, ex)),
+        };
+        self.display.read_area(area_start, area_end, buf)
+    }
 }
 
 #[cfg(feature = "graphics")]
 extern crate embedded_graphics_core;
 #[cfg(feature = "graphics")]
 use self::embedded_graphics_core::pixelcolor::{raw::RawU16, Rgb565};
+#[cfg(all(feature = "graphics", not(feature = "buffered")))]
+use self::embedded_graphics_core::prelude::PointsIter;
 #[cfg(feature = "graphics")]
 use self::embedded_graphics_core::prelude::{
     Dimensions, DrawTarget, OriginDimensions, Pixel, RawData, Size,
 };
-#[cfg(all(feature = "graphics", not(feature = "buffered")))]
-use self::embedded_graphics_core::{prelude::PointsIter, primitives::Rectangle};
+#[cfg(feature = "graphics")]
+use self::embedded_graphics_core::{prelude::Point, primitives::Rectangle};
 
 #[cfg(feature = "graphics")]
-impl<DI: WriteOnlyDataCommand> DrawTarget for GraphicsMode<DI> {
+impl<DI: WriteOnlyDataCommand, RST> DrawTarget for GraphicsMode<DI, RST> {
     type Color = Rgb565;
     type Error = ();
 
+    #[cfg(not(feature = "buffered"))]
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bb = self.bounding_box();
+        let width = bb.size.width as u8;
+
+        // Horizontally adjacent pixels are batched into one window running to the end of the row,
+        // which the RAM pointer keeps advancing through as the chunks are sent
+        let mut chunk = [0u16; 64];
+        let mut len = 0;
+        let mut next: Option<Point> = None;
+        for Pixel(pos, color) in pixels.into_iter().filter(|Pixel(pos, _)| bb.contains(*pos)) {
+            if next != Some(pos) {
+                self.write_pixels(&chunk[..len]).map_err(|_| ())?;
+                len = 0;
+                let (x, y) = (pos.x as u8, pos.y as u8);
+                self.set_window(x, y, width - x, 1).map_err(|_| ())?;
+            } else if len == chunk.len() {
+                self.write_pixels(&chunk).map_err(|_| ())?;
+                len = 0;
+            }
+            chunk[len] = RawU16::from(color).into_inner();
+            len += 1;
+            next = Some(pos + Point::new(1, 0));
+        }
+        self.write_pixels(&chunk[..len]).map_err(|_| ())?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "buffered")]
     fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
     where
         I: IntoIterator<Item = Pixel<Self::Color>>,
@@ -203,7 +1034,9 @@ impl<DI: WriteOnlyDataCommand> DrawTarget for GraphicsMode<DI> {
             DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => ((sy, sx), (ey, ex)),
         };
 
-        self.display.set_draw_area(area_start, area_end).unwrap();
+        self.display
+            .set_draw_area(area_start, area_end)
+            .map_err(|_| ())?;
 
         // Get an iterator of colours as u16
         // Check points for containment
@@ -211,19 +1044,241 @@ impl<DI: WriteOnlyDataCommand> DrawTarget for GraphicsMode<DI> {
             .zip(colors)
             .filter(|(pos, _)| drawable_area.contains(*pos))
             .map(|(_, color)| RawU16::from(color).into_inner())
-            .for_each(|color| {
+            .try_for_each(|color| {
                 self.display
                     .draw(&[(color >> 8) as u8, color as u8])
-                    .unwrap()
+                    .map_err(|_| ())
+            })
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<DI: WriteOnlyDataCommand, RST> GraphicsMode<DI, RST> {
+    /// Fill an area with colours given column by column, top to bottom, like `fill_contiguous`
+    /// does row by row. Unbuffered, the address increment is switched to column major for a
+    /// single window over the visible part of the area and switched back afterwards.
+    pub fn fill_contiguous_column_major<I>(
+        &mut self,
+        area: &Rectangle,
+        colors: I,
+    ) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = Rgb565>,
+    {
+        let drawable_area = area.intersection(&self.bounding_box());
+        if drawable_area.is_zero_sized() {
+            return Ok(());
+        }
+        let columns = area.columns();
+        let points = columns.flat_map(|x| area.rows().map(move |y| Point::new(x, y)));
+        let pixels = points
+            .zip(colors)
+            .filter(|(pos, _)| drawable_area.contains(*pos));
+
+        #[cfg(not(feature = "buffered"))]
+        {
+            // Switch back to row major however the fill ends
+            let result = self.display.set_column_major(true).and_then(|()| {
+                self.set_window(
+                    drawable_area.top_left.x as u8,
+                    drawable_area.top_left.y as u8,
+                    drawable_area.size.width as u8,
+                    drawable_area.size.height as u8,
+                )?;
+                let mut pixels = pixels.map(|(_, color)| RawU16::from(color).into_inner());
+                let mut chunk = [0u16; 64];
+                loop {
+                    let len = chunk
+                        .iter_mut()
+                        .zip(pixels.by_ref())
+                        .map(|(pixel, color)| *pixel = color)
+                        .count();
+                    if len == 0 {
+                        return Ok(());
+                    }
+                    self.write_pixels(&chunk[..len])?;
+                }
             });
+            let restored = self.display.set_column_major(false);
+            result.and(restored)
+        }
 
-        Ok(())
+        #[cfg(feature = "buffered")]
+        {
+            for (pos, color) in pixels {
+                self.set_pixel(pos.x as u32, pos.y as u32, RawU16::from(color).into_inner());
+            }
+            Ok(())
+        }
     }
 }
 
-impl<DI: WriteOnlyDataCommand> OriginDimensions for GraphicsMode<DI> {
+#[cfg(feature = "graphics")]
+impl<DI: WriteOnlyDataCommand, RST> OriginDimensions for GraphicsMode<DI, RST> {
     fn size(&self) -> Size {
-        let dim = self.display.get_size().dimensions();
+        let dim = self.display.get_dimensions();
         Size::from((dim.0 as u32, dim.1 as u32))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{self, Mock};
+    use crate::properties::DisplaySize;
+
+    struct NoDelay;
+
+    impl DelayNs for NoDelay {
+        fn delay_ns(&mut self, _ns: u32) {}
+    }
+
+    /// A 3x3 block
+    const PIXELS: [u16; 9] = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+
+    /// Check the visible part of the block drawn at `x`, `y`. Rotated by 90º or 270º, the
+    /// columns of the GDDRAM are the rows of the screen.
+    fn assert_block(mock: &Mock, x: i32, y: i32, transposed: bool) {
+        for (i, &pixel) in PIXELS.iter().enumerate() {
+            let (px, py) = (x + i as i32 % 3, y + i as i32 / 3);
+            if (0..128).contains(&px) && (0..128).contains(&py) {
+                let (column, row) = if transposed { (py, px) } else { (px, py) };
+                let color = mock.get(column as u8, row as u8);
+                assert_eq!(color, pixel, "pixel {} at {}, {}", i, x, y);
+            }
+        }
+    }
+
+    /// Blit the block at each position, as bytes or as pixels
+    fn blit_at(display: &mut GraphicsMode<Mock>, positions: &[(i32, i32)], as_bytes: bool) {
+        let mut bytes = [0; 18];
+        for (bytes, pixel) in bytes.chunks_exact_mut(2).zip(PIXELS.iter()) {
+            bytes.copy_from_slice(&pixel.to_be_bytes());
+        }
+        for &(x, y) in positions {
+            if as_bytes {
+                display.blit(x, y, 3, 3, &bytes).unwrap();
+            } else {
+                display.blit_pixels(x, y, 3, 3, &PIXELS).unwrap();
+            }
+        }
+        #[cfg(feature = "buffered")]
+        display.flush();
+    }
+
+    #[test]
+    fn blit_clipped() {
+        // Off the top left, off the top with whole rows visible, off the right and the bottom
+        let positions = [(-1, -1), (10, -2), (126, 5), (40, 127)];
+        for as_bytes in [true, false] {
+            let mut display = mock::graphics(DisplaySize::Display128x128, DisplayRotation::Rotate0);
+            blit_at(&mut display, &positions, as_bytes);
+            let mock = mock::release(display);
+            for &(x, y) in positions.iter() {
+                assert_block(&mock, x, y, false);
+            }
+            assert_eq!(mock.get(2, 2), 0);
+            assert_eq!(mock.get(125, 5), 0);
+            #[cfg(not(feature = "buffered"))]
+            assert_eq!(mock.pixels, 4 + 3 + 6 + 3);
+        }
+    }
+
+    #[test]
+    fn blit_rotated() {
+        let positions = [(5, 7), (126, 20)];
+        for as_bytes in [true, false] {
+            let mut display =
+                mock::graphics(DisplaySize::Display128x128, DisplayRotation::Rotate90);
+            display.init().unwrap();
+            blit_at(&mut display, &positions, as_bytes);
+            let mock = mock::release(display);
+            for &(x, y) in positions.iter() {
+                assert_block(&mock, x, y, true);
+            }
+        }
+    }
+
+    #[test]
+    fn fade_sends_every_level() {
+        const MASTER_CURRENT: u8 = 0xC7;
+
+        for (from, to) in [(0, 40), (200, 190)] {
+            let mut display = mock::graphics(DisplaySize::Display128x128, DisplayRotation::Rotate0);
+            display.set_brightness(from).unwrap();
+            display.fade_to(to, 100, &mut NoDelay).unwrap();
+            assert_eq!(display.get_brightness(), to);
+
+            let mock = mock::release(display);
+            let sent = mock
+                .codes()
+                .iter()
+                .filter(|&&code| code == MASTER_CURRENT)
+                .count();
+            assert_eq!(sent, 1 + from.abs_diff(to) as usize);
+        }
+    }
+
+    #[cfg(all(feature = "graphics", not(feature = "buffered")))]
+    mod unbuffered {
+        use super::*;
+        use embedded_graphics_core::prelude::Size;
+
+        fn colors() -> impl Iterator<Item = Rgb565> {
+            (0..6).map(|i| Rgb565::new(i, 0, 0))
+        }
+
+        #[test]
+        fn reset_forgets_address() {
+            let mut display = mock::graphics(DisplaySize::Display128x96, DisplayRotation::Rotate0);
+            display.init().unwrap();
+            display.set_window(0, 0, 2, 2).unwrap();
+            assert!(display.display.draw_area().is_some());
+            display.reset(&mut NoResetPin, &mut NoDelay).unwrap();
+            assert!(display.display.draw_area().is_none());
+        }
+
+        #[test]
+        fn locked_interface_errors() {
+            let mut display = mock::graphics(DisplaySize::Display128x128, DisplayRotation::Rotate0);
+            display.lock_interface().unwrap();
+            assert!(matches!(display.clear(), Err(Error::InterfaceLocked)));
+            let result = display.set_pixel(1, 2, 0xFFFF);
+            assert!(matches!(result, Err(Error::InterfaceLocked)));
+            let area = Rectangle::new(Point::new(1, 2), Size::new(2, 3));
+            assert_eq!(display.fill_contiguous(&area, colors()), Err(()));
+            assert!(display.set_pixel(128, 0, 0xFFFF).is_ok());
+        }
+
+        #[test]
+        fn column_major_fill() {
+            let mut display = mock::graphics(DisplaySize::Display128x128, DisplayRotation::Rotate0);
+            let area = Rectangle::new(Point::new(1, 2), Size::new(2, 3));
+            display
+                .fill_contiguous_column_major(&area, colors())
+                .unwrap();
+            let mock = mock::release(display);
+            for (i, color) in colors().enumerate() {
+                let (x, y) = (1 + i as u8 / 3, 2 + i as u8 % 3);
+                assert_eq!(mock.get(x, y), RawU16::from(color).into_inner());
+            }
+            assert!(!mock.vertical);
+        }
+
+        #[test]
+        fn column_major_fill_restores_increment() {
+            // Fail setting the window, after the remap command and its argument
+            let mut mock = Mock::default();
+            mock.fail_after = Some(2);
+            let mut display =
+                mock::graphics_on(mock, DisplaySize::Display128x128, DisplayRotation::Rotate0);
+            let area = Rectangle::new(Point::new(1, 2), Size::new(2, 3));
+            assert!(display
+                .fill_contiguous_column_major(&area, colors())
+                .is_err());
+            let mock = mock::release(display);
+            assert_eq!(mock.pixels, 0);
+            assert!(!mock.vertical);
+        }
+    }
+}
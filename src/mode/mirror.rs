@@ -0,0 +1,211 @@
+//! The same frame shown on several panels
+
+use crate::error::Error;
+#[cfg(feature = "buffered")]
+use crate::mode::panel::Mirror;
+use crate::mode::panel::Panel;
+
+use embedded_graphics_core::pixelcolor::Rgb565;
+use embedded_graphics_core::prelude::{DrawTarget, OriginDimensions, Pixel, Size};
+use embedded_graphics_core::primitives::Rectangle;
+#[cfg(not(feature = "buffered"))]
+use embedded_graphics_core::{
+    pixelcolor::raw::RawU16,
+    prelude::{Dimensions, PointsIter, RawData},
+};
+
+#[cfg(not(feature = "buffered"))]
+/// A drawing surface which forwards every draw to `N` panels of the same size. Panels can have
+/// different interfaces and rotations, a panel with another rotation shows the frame rotated,
+/// e.g. when it is mounted upside down.
+///
+/// The panels are borrowed, e.g. `[&mut front, &mut back]`, drop the surface to change their
+/// settings.
+pub struct MirrorDisplay<'a, const N: usize> {
+    panels: [&'a mut dyn Panel; N],
+}
+
+#[cfg(feature = "buffered")]
+/// A drawing surface which renders into the framebuffer of one panel and flushes it, shifted by
+/// the panel's burn-in offset, to `N` more displays of the same size. The mirrors need no
+/// framebuffer of their own, they are created with [`Display::new`](../../display/struct.Display.html#method.new)
+/// and initialised with [`Display::init`](../../display/struct.Display.html#method.init). The
+/// panel and mirrors can have different interfaces and rotations, a mirror with another rotation
+/// shows the frame rotated, e.g. when it is mounted upside down.
+///
+/// The panel and mirrors are borrowed, drop the surface to change their settings.
+pub struct MirrorDisplay<'a, const N: usize> {
+    panel: &'a mut dyn Panel,
+    mirrors: [&'a mut dyn Mirror; N],
+}
+
+#[cfg(not(feature = "buffered"))]
+impl<'a, const N: usize> MirrorDisplay<'a, N> {
+    /// Mirror the output to the given panels.
+    ///
+    /// Returns [`Error::OutOfRange`] unless every panel has the same width and height after
+    /// rotation, e.g. 128x96 panels must all be rotated by 0º or 180º, or all by 90º or 270º.
+    pub fn new(panels: [&'a mut dyn Panel; N]) -> Result<Self, Error> {
+        if let Some(first) = panels.first() {
+            let dimensions = first.dimensions();
+            if panels.iter().any(|panel| panel.dimensions() != dimensions) {
+                return Err(Error::OutOfRange);
+            }
+        }
+        Ok(MirrorDisplay { panels })
+    }
+
+    /// Clear every panel
+    pub fn clear(&mut self) -> Result<(), Error> {
+        for panel in self.panels.iter_mut() {
+            panel.clear_panel()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "buffered")]
+impl<'a, const N: usize> MirrorDisplay<'a, N> {
+    /// Render into `panel` and mirror its framebuffer to `mirrors` on every flush.
+    ///
+    /// Returns [`Error::OutOfRange`] unless every mirror has the width and height of the panel
+    /// after rotation, e.g. 128x96 displays must all be rotated by 0º or 180º, or all by 90º or
+    /// 270º.
+    pub fn new(panel: &'a mut dyn Panel, mirrors: [&'a mut dyn Mirror; N]) -> Result<Self, Error> {
+        let dimensions = panel.dimensions();
+        if mirrors
+            .iter()
+            .any(|mirror| mirror.dimensions() != dimensions)
+        {
+            return Err(Error::OutOfRange);
+        }
+        Ok(MirrorDisplay { panel, mirrors })
+    }
+
+    /// Clear the framebuffer
+    pub fn clear(&mut self, flush: bool) {
+        self.panel.clear_panel(false);
+        if flush {
+            self.flush();
+        }
+    }
+
+    /// Send the framebuffer to the rendering panel and to each mirror in turn
+    pub fn flush(&mut self) {
+        self.panel.flush_panel();
+        let (frame, offset) = self.panel.frame();
+        for mirror in self.mirrors.iter_mut() {
+            mirror.show_frame(frame, offset).unwrap();
+        }
+    }
+}
+
+#[cfg(not(feature = "buffered"))]
+impl<const N: usize> DrawTarget for MirrorDisplay<'_, N> {
+    type Color = Rgb565;
+    type Error = ();
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for pixel in pixels {
+            for panel in self.panels.iter_mut() {
+                panel.draw_pixels(&mut core::iter::once(pixel))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let drawable_area = area.intersection(&self.bounding_box());
+        if drawable_area != *area {
+            // Clipped areas are rare, send their visible pixels one by one
+            let pixels = area
+                .points()
+                .zip(colors)
+                .filter(|(pos, _)| drawable_area.contains(*pos))
+                .map(|(pos, color)| Pixel(pos, color));
+            return self.draw_iter(pixels);
+        }
+        if area.is_zero_sized() {
+            return Ok(());
+        }
+
+        // Open the window on every panel once, then stream the colours to all of them in chunks
+        let (x, y) = (area.top_left.x as u8, area.top_left.y as u8);
+        let (width, height) = (area.size.width as u8, area.size.height as u8);
+        for panel in self.panels.iter_mut() {
+            panel.open_window(x, y, width, height).map_err(|_| ())?;
+        }
+        let mut colors = colors
+            .into_iter()
+            .map(|color| RawU16::from(color).into_inner());
+        let mut chunk = [0u16; 64];
+        loop {
+            let len = chunk
+                .iter_mut()
+                .zip(colors.by_ref())
+                .map(|(pixel, color)| *pixel = color)
+                .count();
+            if len == 0 {
+                break;
+            }
+            for panel in self.panels.iter_mut() {
+                panel.write_window(&chunk[..len]).map_err(|_| ())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        for panel in self.panels.iter_mut() {
+            panel.fill_area_solid(area, color)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "buffered")]
+impl<const N: usize> DrawTarget for MirrorDisplay<'_, N> {
+    type Color = Rgb565;
+    type Error = ();
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.panel.draw_pixels(&mut pixels.into_iter())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.panel.fill_area(area, &mut colors.into_iter())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.panel.fill_area_solid(area, color)
+    }
+}
+
+impl<const N: usize> OriginDimensions for MirrorDisplay<'_, N> {
+    #[cfg(not(feature = "buffered"))]
+    fn size(&self) -> Size {
+        self.panels
+            .first()
+            .map(|panel| panel.dimensions())
+            .map(|(width, height)| Size::new(width as u32, height as u32))
+            .unwrap_or_else(Size::zero)
+    }
+
+    #[cfg(feature = "buffered")]
+    fn size(&self) -> Size {
+        let (width, height) = self.panel.dimensions();
+        Size::new(width as u32, height as u32)
+    }
+}
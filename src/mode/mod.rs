@@ -7,8 +7,22 @@
 pub mod graphics;
 // pub mod terminal;
 pub mod displaymode;
+#[cfg(feature = "graphics")]
+pub mod mirror;
+#[cfg(feature = "graphics")]
+pub mod multi;
+#[cfg(feature = "graphics")]
+pub mod panel;
 pub mod raw;
 
 pub use self::graphics::GraphicsMode;
+#[cfg(feature = "graphics")]
+pub use self::mirror::MirrorDisplay;
+#[cfg(feature = "graphics")]
+pub use self::multi::MultiDisplay;
+#[cfg(all(feature = "graphics", feature = "buffered"))]
+pub use self::panel::Mirror;
+#[cfg(feature = "graphics")]
+pub use self::panel::Panel;
 // pub use self::terminal::TerminalMode;
 pub use self::raw::RawMode;
@@ -0,0 +1,318 @@
+//! Several panels combined into one drawing surface
+
+#[cfg(not(feature = "buffered"))]
+use crate::error::Error;
+use crate::mode::panel::Panel;
+
+use embedded_graphics_core::pixelcolor::Rgb565;
+use embedded_graphics_core::prelude::{Dimensions, DrawTarget, Pixel, Point, Size};
+use embedded_graphics_core::primitives::Rectangle;
+
+/// A virtual canvas tiled from `N` panels, each placed with its top left corner at a position on
+/// the canvas. Panels can have different sizes, rotations and interfaces, pixels outside of every
+/// panel are dropped and where panels overlap the first one in the list is drawn to.
+///
+/// The panels are borrowed, e.g. `[(&mut left, Point::zero()), (&mut right, Point::new(128,
+/// 0))]`, drop the canvas to change their settings.
+pub struct MultiDisplay<'a, const N: usize> {
+    panels: [(&'a mut dyn Panel, Point); N],
+}
+
+impl<'a, const N: usize> MultiDisplay<'a, N> {
+    /// Combine panels with the canvas positions of their top left corners
+    pub fn new(panels: [(&'a mut dyn Panel, Point); N]) -> Self {
+        MultiDisplay { panels }
+    }
+
+    /// Get the area of the canvas covered by a panel
+    pub fn panel_area(&self, index: usize) -> Rectangle {
+        let (panel, position) = &self.panels[index];
+        let (width, height) = panel.dimensions();
+        Rectangle::new(*position, Size::new(width as u32, height as u32))
+    }
+
+    #[cfg(not(feature = "buffered"))]
+    /// Clear every panel
+    pub fn clear(&mut self) -> Result<(), Error> {
+        for (panel, _) in self.panels.iter_mut() {
+            panel.clear_panel()?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "buffered")]
+    /// Clear every panel
+    pub fn clear(&mut self, flush: bool) {
+        for (panel, _) in self.panels.iter_mut() {
+            panel.clear_panel(flush);
+        }
+    }
+
+    #[cfg(feature = "buffered")]
+    /// Flush the framebuffer of every panel
+    pub fn flush(&mut self) {
+        for (panel, _) in self.panels.iter_mut() {
+            panel.flush_panel();
+        }
+    }
+
+    /// Find the first panel containing a point of the canvas
+    fn panel_at(&self, point: Point) -> Option<usize> {
+        (0..N).find(|&i| self.panel_area(i).contains(point))
+    }
+
+    /// Get the panel drawn to at `point` and where the run of canvas pixels along the row which
+    /// go to the same panel ends, at most at `right`
+    fn run_at(&self, point: Point, right: i32) -> (Option<usize>, i32) {
+        let panel = self.panel_at(point);
+        // Panels before this one take over where they start, after a gap any panel does
+        let end = (0..panel.unwrap_or(N))
+            .map(|i| self.panel_area(i))
+            .filter(|area| {
+                let top = area.top_left.y;
+                point.y >= top && point.y < top + area.size.height as i32
+            })
+            .map(|area| area.top_left.x)
+            .filter(|&left| left > point.x)
+            .fold(right, i32::min);
+        let end = match panel {
+            Some(i) => {
+                let area = self.panel_area(i);
+                end.min(area.top_left.x + area.size.width as i32)
+            }
+            None => end,
+        };
+        (panel, end)
+    }
+
+    /// Get the single panel an area is drawn to, if it is entirely on the first panel it
+    /// touches
+    fn single_panel(&self, area: &Rectangle) -> Option<usize> {
+        let first = (0..N).find(|&i| !self.panel_area(i).intersection(area).is_zero_sized())?;
+        (self.panel_area(first).intersection(area) == *area).then_some(first)
+    }
+}
+
+impl<const N: usize> Dimensions for MultiDisplay<'_, N> {
+    /// The smallest rectangle containing every panel
+    fn bounding_box(&self) -> Rectangle {
+        let mut areas = (0..N)
+            .map(|i| self.panel_area(i))
+            .filter(|area| !area.is_zero_sized());
+        let first = match areas.next() {
+            Some(area) => area,
+            None => return Rectangle::zero(),
+        };
+        let (top_left, bottom_right) = areas.fold(
+            (first.top_left, first.top_left + first.size),
+            |(tl, br), area| {
+                (
+                    tl.component_min(area.top_left),
+                    br.component_max(area.top_left + area.size),
+                )
+            },
+        );
+        Rectangle::with_corners(top_left, bottom_right - Point::new(1, 1))
+    }
+}
+
+impl<const N: usize> DrawTarget for MultiDisplay<'_, N> {
+    type Color = Rgb565;
+    type Error = ();
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        // Consecutive pixels on the same panel are drawn in one go, so the panel can batch them
+        let areas: [Rectangle; N] = core::array::from_fn(|i| self.panel_area(i));
+        let panel_at = |point: Point| areas.iter().position(|area| area.contains(point));
+        let mut pixels = pixels.into_iter().peekable();
+        while let Some(Pixel(point, color)) = pixels.next() {
+            let i = match panel_at(point) {
+                Some(i) => i,
+                None => continue,
+            };
+            let (panel, position) = &mut self.panels[i];
+            let position = *position;
+            let mut run = core::iter::once(Pixel(point, color))
+                .chain(core::iter::from_fn(|| {
+                    pixels.next_if(|Pixel(point, _)| panel_at(*point) == Some(i))
+                }))
+                .map(|Pixel(point, color)| Pixel(point - position, color));
+            panel.draw_pixels(&mut run)?;
+        }
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        // The common case of an area on a single panel keeps its single window
+        if let Some(i) = self.single_panel(area) {
+            let (panel, position) = &mut self.panels[i];
+            let local = Rectangle::new(area.top_left - *position, area.size);
+            return panel.fill_area(&local, &mut colors.into_iter());
+        }
+
+        // Otherwise every row is split into the runs going to each panel
+        let mut colors = colors.into_iter();
+        let right = area.top_left.x + area.size.width as i32;
+        for y in area.rows() {
+            let mut x = area.top_left.x;
+            while x < right {
+                let (panel, end) = self.run_at(Point::new(x, y), right);
+                let len = (end - x) as usize;
+                match panel {
+                    Some(i) => {
+                        let (panel, position) = &mut self.panels[i];
+                        let run =
+                            Rectangle::new(Point::new(x, y) - *position, Size::new(len as u32, 1));
+                        panel.fill_area(&run, &mut colors.by_ref().take(len))?;
+                    }
+                    None => {
+                        colors.by_ref().take(len).for_each(drop);
+                    }
+                }
+                x = end;
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        for i in 0..N {
+            let drawable = area.intersection(&self.panel_area(i));
+            if drawable.is_zero_sized() {
+                continue;
+            }
+            let position = self.panels[i].1;
+            let covered =
+                (0..i).any(|j| !self.panel_area(j).intersection(&drawable).is_zero_sized());
+            if !covered {
+                let local = Rectangle::new(drawable.top_left - position, drawable.size);
+                self.panels[i].0.fill_area_solid(&local, color)?;
+                continue;
+            }
+
+            // Leave out the parts of the panel's share which panels before it cover
+            let right = drawable.top_left.x + drawable.size.width as i32;
+            for y in drawable.rows() {
+                let mut x = drawable.top_left.x;
+                while x < right {
+                    let (panel, end) = self.run_at(Point::new(x, y), right);
+                    if panel == Some(i) {
+                        let size = Size::new((end - x) as u32, 1);
+                        let run = Rectangle::new(Point::new(x, y) - position, size);
+                        self.panels[i].0.fill_area_solid(&run, color)?;
+                    }
+                    x = end;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{self, Mock};
+    use crate::mode::graphics::GraphicsMode;
+    use crate::properties::{DisplayRotation, DisplaySize};
+    use embedded_graphics_core::pixelcolor::raw::{RawData, RawU16};
+    use embedded_graphics_core::pixelcolor::RgbColor;
+
+    fn panel() -> GraphicsMode<Mock> {
+        mock::graphics(DisplaySize::Display128x128, DisplayRotation::Rotate0)
+    }
+
+    #[allow(unused_mut)]
+    fn release(mut panel: GraphicsMode<Mock>) -> Mock {
+        #[cfg(feature = "buffered")]
+        panel.flush();
+        mock::release(panel)
+    }
+
+    fn raw(color: Rgb565) -> u16 {
+        RawU16::from(color).into_inner()
+    }
+
+    #[test]
+    fn draw_routes_pixels() {
+        let (mut left, mut right) = (panel(), panel());
+        let mut multi = MultiDisplay::new([
+            (&mut left as &mut dyn Panel, Point::zero()),
+            (&mut right, Point::new(100, 0)),
+        ]);
+        let pixels = [
+            Pixel(Point::new(1, 1), Rgb565::RED),
+            Pixel(Point::new(110, 2), Rgb565::GREEN),
+            Pixel(Point::new(130, 3), Rgb565::BLUE),
+            Pixel(Point::new(300, 0), Rgb565::WHITE),
+        ];
+        multi.draw_iter(pixels).unwrap();
+        // A run of pixels on one panel is drawn into a single window
+        multi
+            .draw_iter((0..8).map(|x| Pixel(Point::new(140 + x, 5), Rgb565::WHITE)))
+            .unwrap();
+
+        let (left, right) = (release(left), release(right));
+        assert_eq!(left.get(1, 1), raw(Rgb565::RED));
+        // Overlapping, the first panel is drawn to
+        assert_eq!(left.get(110, 2), raw(Rgb565::GREEN));
+        assert_eq!(right.get(10, 2), 0);
+        assert_eq!(right.get(30, 3), raw(Rgb565::BLUE));
+        for x in 40..48 {
+            assert_eq!(right.get(x, 5), raw(Rgb565::WHITE));
+        }
+        #[cfg(not(feature = "buffered"))]
+        assert_eq!(right.codes().iter().filter(|&&c| c == 0x15).count(), 2);
+    }
+
+    #[test]
+    fn fill_contiguous_priority() {
+        let (mut left, mut right) = (panel(), panel());
+        // The right panel comes first, so it is drawn to where they overlap
+        let mut multi = MultiDisplay::new([
+            (&mut right as &mut dyn Panel, Point::new(100, 0)),
+            (&mut left, Point::zero()),
+        ]);
+        let area = Rectangle::new(Point::new(98, 0), Size::new(4, 2));
+        let colors = (0..8).map(|i| Rgb565::new(i + 1, 0, 0));
+        multi.fill_contiguous(&area, colors).unwrap();
+
+        let (left, right) = (release(left), release(right));
+        let color = |i: u8| raw(Rgb565::new(i + 1, 0, 0));
+        for y in 0..2 {
+            for x in 0..2 {
+                assert_eq!(left.get(98 + x, y), color(y * 4 + x));
+                assert_eq!(right.get(x, y), color(y * 4 + x + 2));
+            }
+            assert_eq!(left.get(100, y), 0);
+            assert_eq!(right.get(2, y), 0);
+        }
+    }
+
+    #[test]
+    fn fill_solid_priority() {
+        let (mut left, mut right) = (panel(), panel());
+        let mut multi = MultiDisplay::new([
+            (&mut right as &mut dyn Panel, Point::new(100, 0)),
+            (&mut left, Point::zero()),
+        ]);
+        let area = Rectangle::new(Point::new(98, 0), Size::new(4, 2));
+        multi.fill_solid(&area, Rgb565::RED).unwrap();
+
+        let (left, right) = (release(left), release(right));
+        for y in 0..2 {
+            for x in 0..2 {
+                assert_eq!(left.get(98 + x, y), raw(Rgb565::RED));
+                assert_eq!(right.get(x, y), raw(Rgb565::RED));
+            }
+            assert_eq!(left.get(100, y), 0);
+            assert_eq!(right.get(2, y), 0);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Panels of combined drawing surfaces
+//!
+//! [`MultiDisplay`](../multi/struct.MultiDisplay.html) and
+//! [`MirrorDisplay`](../mirror/struct.MirrorDisplay.html) borrow their panels as `&mut dyn Panel`,
+//! so panels on different interfaces, e.g. two SPI buses, can be combined. With the `buffered`
+//! feature the mirrors of a `MirrorDisplay` are borrowed as `&mut dyn Mirror`.
+
+#[cfg(feature = "buffered")]
+use crate::display::Display;
+use crate::error::Error;
+use crate::mode::graphics::GraphicsMode;
+use display_interface::WriteOnlyDataCommand;
+
+use embedded_graphics_core::pixelcolor::Rgb565;
+use embedded_graphics_core::prelude::{DrawTarget, Pixel};
+use embedded_graphics_core::primitives::Rectangle;
+
+/// A display which combined drawing surfaces draw to, implemented by [`GraphicsMode`]
+pub trait Panel {
+    /// Get the width and height of the panel in its rotated coordinates
+    fn dimensions(&self) -> (u8, u8);
+
+    /// Draw individual pixels, like `DrawTarget::draw_iter`
+    fn draw_pixels(&mut self, pixels: &mut dyn Iterator<Item = Pixel<Rgb565>>) -> Result<(), ()>;
+
+    /// Fill an area with colours row by row, like `DrawTarget::fill_contiguous`
+    fn fill_area(
+        &mut self,
+        area: &Rectangle,
+        colors: &mut dyn Iterator<Item = Rgb565>,
+    ) -> Result<(), ()>;
+
+    /// Fill an area with one colour, like `DrawTarget::fill_solid`
+    fn fill_area_solid(&mut self, area: &Rectangle, color: Rgb565) -> Result<(), ()>;
+
+    #[cfg(not(feature = "buffered"))]
+    /// Set the hardware window to an area which is on the panel
+    fn open_window(&mut self, x: u8, y: u8, width: u8, height: u8) -> Result<(), Error>;
+
+    #[cfg(not(feature = "buffered"))]
+    /// Send Rgb565 pixels into the window set with `open_window`
+    fn write_window(&mut self, pixels: &[u16]) -> Result<(), Error>;
+
+    #[cfg(not(feature = "buffered"))]
+    /// Clear the panel
+    fn clear_panel(&mut self) -> Result<(), Error>;
+
+    #[cfg(feature = "buffered")]
+    /// Clear the framebuffer, sending it to the panel if `flush` is set
+    fn clear_panel(&mut self, flush: bool);
+
+    #[cfg(feature = "buffered")]
+    /// Send the framebuffer to the panel
+    fn flush_panel(&mut self);
+
+    #[cfg(feature = "buffered")]
+    /// Get the framebuffer and the burn-in offset it is shown with
+    fn frame(&self) -> (&[u8], (i8, i8));
+}
+
+impl<DI, RST> Panel for GraphicsMode<DI, RST>
+where
+    DI: WriteOnlyDataCommand,
+{
+    fn dimensions(&self) -> (u8, u8) {
+        self.get_dimensions()
+    }
+
+    fn draw_pixels(&mut self, pixels: &mut dyn Iterator<Item = Pixel<Rgb565>>) -> Result<(), ()> {
+        self.draw_iter(pixels)
+    }
+
+    fn fill_area(
+        &mut self,
+        area: &Rectangle,
+        colors: &mut dyn Iterator<Item = Rgb565>,
+    ) -> Result<(), ()> {
+        self.fill_contiguous(area, colors)
+    }
+
+    fn fill_area_solid(&mut self, area: &Rectangle, color: Rgb565) -> Result<(), ()> {
+        self.fill_solid(area, color)
+    }
+
+    #[cfg(not(feature = "buffered"))]
+    fn open_window(&mut self, x: u8, y: u8, width: u8, height: u8) -> Result<(), Error> {
+        self.set_window(x, y, width, height)
+    }
+
+    #[cfg(not(feature = "buffered"))]
+    fn write_window(&mut self, pixels: &[u16]) -> Result<(), Error> {
+        self.write_pixels(pixels)
+    }
+
+    #[cfg(not(feature = "buffered"))]
+    fn clear_panel(&mut self) -> Result<(), Error> {
+        self.clear()
+    }
+
+    #[cfg(feature = "buffered")]
+    fn clear_panel(&mut self, flush: bool) {
+        self.clear(flush);
+    }
+
+    #[cfg(feature = "buffered")]
+    fn flush_panel(&mut self) {
+        self.flush();
+    }
+
+    #[cfg(feature = "buffered")]
+    fn frame(&self) -> (&[u8], (i8, i8)) {
+        (self.fb(), self.offset)
+    }
+}
+
+#[cfg(feature = "buffered")]
+/// A display without a framebuffer which shows the frames of another panel, implemented by
+/// [`Display`]
+pub trait Mirror {
+    /// Get the width and height of the display in its rotated coordinates
+    fn dimensions(&self) -> (u8, u8);
+
+    /// Send a whole frame of big endian Rgb565 pixels shifted by a burn-in offset
+    fn show_frame(&mut self, frame: &[u8], offset: (i8, i8)) -> Result<(), Error>;
+}
+
+#[cfg(feature = "buffered")]
+impl<DI, RST> Mirror for Display<DI, RST>
+where
+    DI: WriteOnlyDataCommand,
+{
+    fn dimensions(&self) -> (u8, u8) {
+        self.get_dimensions()
+    }
+
+    fn show_frame(&mut self, frame: &[u8], offset: (i8, i8)) -> Result<(), Error> {
+        self.draw_frame(frame, offset)
+    }
+}
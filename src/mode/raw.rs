@@ -4,52 +4,84 @@
 //! builder. Used as a source to coerce the driver into richer modes like
 //! [`GraphicsMode`](../graphics/index.html) and [`TerminalMode`](../terminal/index.html).
 
+use crate::command::Command;
 use crate::display::Display;
+use crate::error::Error;
+use crate::interface::NoResetPin;
 use display_interface::WriteOnlyDataCommand;
 
 use crate::mode::displaymode::DisplayModeTrait;
 
 /// Raw display mode
-pub struct RawMode<DI>
+pub struct RawMode<DI, RST = NoResetPin>
 where
     DI: WriteOnlyDataCommand,
 {
-    pub display: Display<DI>,
+    pub display: Display<DI, RST>,
     #[cfg(feature = "buffered")]
     pub buffer: &'static mut [u8],
 }
 
-impl<DI> DisplayModeTrait<DI> for RawMode<DI>
+impl<DI, RST> DisplayModeTrait<DI, RST> for RawMode<DI, RST>
 where
     DI: WriteOnlyDataCommand,
 {
     /// Create new RawMode instance
     #[cfg(not(feature = "buffered"))]
-    fn new(display: Display<DI>) -> Self {
+    fn new(display: Display<DI, RST>) -> Self {
         RawMode { display }
     }
 
     #[cfg(feature = "buffered")]
-    fn new(display: Display<DI>, buffer: &'static mut [u8]) -> Self {
+    fn new(display: Display<DI, RST>, buffer: &'static mut [u8]) -> Self {
         RawMode { display, buffer }
     }
 
     #[cfg(not(feature = "buffered"))]
     /// Release all resources used by RawMode
-    fn release(self) -> Display<DI> {
+    fn release(self) -> Display<DI, RST> {
         self.display
     }
 
     #[cfg(feature = "buffered")]
     /// Release all resources used by RawMode
-    fn release(self) -> (Display<DI>, &'static mut [u8]) {
+    fn release(self) -> (Display<DI, RST>, &'static mut [u8]) {
         (self.display, self.buffer)
     }
 }
 
 // impl<DI: DisplayInterface> RawMode<DI> {
 //     /// Create a new raw display mode
-//     pub fn new(display: Display<DI>) -> Self {
+//     pub fn new(display: Display<DI, RST>) -> Self {
 //         RawMode { display }
 //     }
 // }
+
+impl<DI, RST> RawMode<DI, RST>
+where
+    DI: WriteOnlyDataCommand,
+{
+    /// Send a typed command to the display. Protected commands are unlocked around the command
+    /// if they are locked.
+    pub fn send(&mut self, command: Command) -> Result<(), Error> {
+        self.display.send_unlocked(command)
+    }
+
+    /// Send a raw command byte followed by its argument bytes to the display, for commands the
+    /// driver doesn't wrap. Protected commands are unlocked around the command if they are
+    /// locked, and command lock (0xFD) commands are tracked like their typed equivalents.
+    pub fn send_command(&mut self, command: u8, data: &[u8]) -> Result<(), Error> {
+        self.display.send_raw(command, data)
+    }
+
+    /// Set the window of the display RAM written by `write_pixels`, from `start` (inclusive) to
+    /// `end` (exclusive) in RAM coordinates
+    pub fn set_window(&mut self, start: (u8, u8), end: (u8, u8)) -> Result<(), Error> {
+        self.display.set_draw_area(start, end)
+    }
+
+    /// Write Rgb565 pixels at the current position in the window
+    pub fn write_pixels(&mut self, pixels: &[u16]) -> Result<(), Error> {
+        self.display.draw_pixels(pixels)
+    }
+}
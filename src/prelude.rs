@@ -5,4 +5,5 @@ pub const SSD1351_SPI_MODE: Mode = Mode {
     polarity: Polarity::IdleLow,
 };
 
+pub use crate::interface::ThreeWireSPIInterface;
 pub use display_interface_spi::SPIInterface;
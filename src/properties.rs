@@ -1,5 +1,7 @@
 //! Display attributes
 
+use crate::error::Error;
+
 /// Display rotation.
 ///
 /// Note that 90º and 270º rotations are not supported by
@@ -41,3 +43,236 @@ impl DisplaySize {
         w as usize * h as usize
     }
 }
+
+/// Mode of one of the two general purpose pins (GPIO0 and GPIO1) of the controller
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum GpioMode {
+    /// Pin is high impedance, input disabled
+    HiZInputDisabled,
+    /// Pin is high impedance, input enabled
+    HiZInputEnabled,
+    /// Pin driven low
+    OutputLow,
+    /// Pin driven high
+    OutputHigh,
+}
+
+impl GpioMode {
+    /// Get the two bit field used by the Set GPIO (0xB5) command
+    pub(crate) fn bits(self) -> u8 {
+        match self {
+            GpioMode::HiZInputDisabled => 0b00,
+            GpioMode::HiZInputEnabled => 0b01,
+            GpioMode::OutputLow => 0b10,
+            GpioMode::OutputHigh => 0b11,
+        }
+    }
+}
+
+/// Command lock state set by the Set Command Lock (0xFD) command
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LockState {
+    /// The MCU interface accepts commands and memory access
+    InterfaceUnlocked,
+    /// The MCU interface ignores all commands and memory access except unlocking it
+    InterfaceLocked,
+    /// The protected commands (display offset, phase length, clock divider, precharge voltage,
+    /// VCOMH and contrast) are ignored
+    ProtectedLocked,
+    /// The protected commands are accepted while the MCU interface is unlocked
+    ProtectedUnlocked,
+}
+
+impl LockState {
+    /// Get the argument byte of the Set Command Lock (0xFD) command
+    pub(crate) fn bits(self) -> u8 {
+        match self {
+            LockState::InterfaceUnlocked => 0x12,
+            LockState::InterfaceLocked => 0x16,
+            LockState::ProtectedLocked => 0xB0,
+            LockState::ProtectedUnlocked => 0xB1,
+        }
+    }
+}
+
+/// Length of the reset (phase 1) and first precharge (phase 2) periods of the segment waveform
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct PhaseLength {
+    phase1: u8,
+    phase2: u8,
+}
+
+impl PhaseLength {
+    /// Create a new phase length from the phase 1 period, an odd number of DCLKs from 5 to 31,
+    /// and the phase 2 period, 3 to 15 DCLKs.
+    pub fn new(phase1: u8, phase2: u8) -> Result<Self, Error> {
+        if !(5..=31).contains(&phase1) || phase1 & 1 == 0 || !(3..=15).contains(&phase2) {
+            return Err(Error::OutOfRange);
+        }
+        Ok(Self { phase1, phase2 })
+    }
+
+    /// Get the phase 1 period in DCLKs
+    pub fn phase1(&self) -> u8 {
+        self.phase1
+    }
+
+    /// Get the phase 2 period in DCLKs
+    pub fn phase2(&self) -> u8 {
+        self.phase2
+    }
+
+    /// Get the argument byte of the Set Phase Length (0xB1) command
+    pub(crate) fn bits(self) -> u8 {
+        (self.phase2 << 4) | ((self.phase1 - 1) / 2)
+    }
+}
+
+/// Display clock setting: the oscillator frequency step and the power of two the display clock
+/// is divided from it by
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct ClockDivider {
+    frequency: u8,
+    divide: u8,
+}
+
+impl ClockDivider {
+    /// Nominal oscillator frequency in Hz of each frequency step. The datasheet lists the
+    /// oscillator frequency as TBD, so these are estimates, stepping linearly from 1.6 MHz,
+    /// and the real frequency of a panel can differ by several percent.
+    pub const NOMINAL_FOSC_HZ: [u32; 16] = [
+        1_600_000, 1_710_000, 1_820_000, 1_930_000, 2_040_000, 2_150_000, 2_260_000, 2_370_000,
+        2_480_000, 2_590_000, 2_700_000, 2_810_000, 2_920_000, 3_030_000, 3_140_000, 3_250_000,
+    ];
+
+    /// Create a new clock setting from an oscillator frequency step from 0 to 15 and the
+    /// exponent of the divide ratio, 0 (divide by 1) to 10 (divide by 1024)
+    pub fn new(frequency: u8, divide: u8) -> Result<Self, Error> {
+        if frequency > 15 || divide > 10 {
+            return Err(Error::OutOfRange);
+        }
+        Ok(Self { frequency, divide })
+    }
+
+    /// Get the oscillator frequency step
+    pub fn frequency(&self) -> u8 {
+        self.frequency
+    }
+
+    /// Get the divide ratio
+    pub fn divisor(&self) -> u16 {
+        1 << self.divide
+    }
+
+    /// Get the nominal display clock in Hz
+    pub fn nominal_dclk_hz(&self) -> u32 {
+        Self::NOMINAL_FOSC_HZ[self.frequency as usize] >> self.divide
+    }
+
+    /// Get the argument byte of the Clock Divider (0xB3) command
+    pub(crate) fn bits(self) -> u8 {
+        (self.frequency << 4) | self.divide
+    }
+}
+
+/// First precharge voltage level, from 0.20 x VCC (level 0) to 0.60 x VCC (level 31)
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct PrechargeVoltage(u8);
+
+impl PrechargeVoltage {
+    /// Create a new precharge voltage from a level between 0 and 31
+    pub fn new(level: u8) -> Result<Self, Error> {
+        if level > 0x1F {
+            return Err(Error::OutOfRange);
+        }
+        Ok(Self(level))
+    }
+
+    /// Get the precharge voltage level
+    pub fn level(&self) -> u8 {
+        self.0
+    }
+}
+
+/// Length of the second precharge (phase 3) period
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SecondPrechargePeriod(u8);
+
+impl SecondPrechargePeriod {
+    /// Create a new second precharge period from a length of 1 to 15 DCLKs
+    pub fn new(dclks: u8) -> Result<Self, Error> {
+        if !(1..=15).contains(&dclks) {
+            return Err(Error::OutOfRange);
+        }
+        Ok(Self(dclks))
+    }
+
+    /// Get the second precharge period in DCLKs
+    pub fn dclks(&self) -> u8 {
+        self.0
+    }
+}
+
+/// Source of the segment low voltage (VSL)
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Vsl {
+    /// External VSL, requires the external circuit between VSL and VSS
+    External,
+    /// Internal VSL, the VSL pin is left unconnected
+    Internal,
+}
+
+impl Vsl {
+    /// Get the first argument byte of the Set Segment Low Voltage (0xB4) command
+    pub(crate) fn bits(self) -> u8 {
+        match self {
+            Vsl::External => 0xA0,
+            Vsl::Internal => 0xA2,
+        }
+    }
+}
+
+/// Data bus connecting the controller to the MCU. The bus type itself (4-wire or 3-wire SPI,
+/// 6800 or 8080 parallel) is selected by the BS pins of the controller, this only tells the driver
+/// how wide the bus is.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DataBus {
+    /// 4-wire or 3-wire serial interface
+    Serial,
+    /// 8 bit 6800 or 8080 parallel interface
+    Parallel8Bit,
+    /// 16 bit 6800 or 8080 parallel interface, pixels are sent as one 16 bit word each
+    Parallel16Bit,
+}
+
+impl DataBus {
+    /// Get the interface selection bits of the Function Selection (0xAB) command
+    pub(crate) fn bits(self) -> u8 {
+        match self {
+            DataBus::Serial | DataBus::Parallel8Bit => 0b00 << 6,
+            DataBus::Parallel16Bit => 0b01 << 6,
+        }
+    }
+}
+
+/// Timings of the hardware reset sequence: the RST pin is driven high for `before_us`, low for
+/// `low_us` and high again, followed by a wait of `after_us` before the display is initialised
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct ResetTiming {
+    /// Time the RST pin is held high before the reset pulse, in microseconds
+    pub before_us: u32,
+    /// Length of the reset pulse, at least 2 microseconds
+    pub low_us: u32,
+    /// Time waited after the reset pulse before sending commands, in microseconds
+    pub after_us: u32,
+}
+
+impl Default for ResetTiming {
+    fn default() -> Self {
+        Self {
+            before_us: 1_000,
+            low_us: 10_000,
+            after_us: 0,
+        }
+    }
+}
@@ -0,0 +1,272 @@
+//! Decoding of images in the [QOI](https://qoiformat.org) format
+//!
+//! QOI images decode in a single pass with nothing but a 64 entry colour cache, so they are
+//! streamed straight to the display by
+//! [`GraphicsMode::draw_qoi`](../mode/graphics/struct.GraphicsMode.html#method.draw_qoi).
+
+use crate::error::Error;
+
+const MAGIC: &[u8; 4] = b"qoif";
+const HEADER_LEN: usize = 14;
+
+const OP_RGB: u8 = 0xFE;
+const OP_RGBA: u8 = 0xFF;
+const OP_INDEX: u8 = 0x00;
+const OP_DIFF: u8 = 0x40;
+const OP_LUMA: u8 = 0x80;
+const OP_RUN: u8 = 0xC0;
+const MASK: u8 = 0xC0;
+
+/// Errors of QOI decoding
+#[derive(Clone, Debug)]
+pub enum QoiError {
+    /// The data does not start with a valid QOI header
+    Header,
+    /// The data ends before all pixels are decoded
+    Truncated,
+    /// Drawing the image failed
+    Display(Error),
+}
+
+impl From<Error> for QoiError {
+    fn from(error: Error) -> Self {
+        QoiError::Display(error)
+    }
+}
+
+/// A QOI image in memory
+#[derive(Clone, Copy, Debug)]
+pub struct QoiImage<'a> {
+    width: u32,
+    height: u32,
+    channels: u8,
+    data: &'a [u8],
+}
+
+impl<'a> QoiImage<'a> {
+    /// Parse the header of a QOI file
+    pub fn new(data: &'a [u8]) -> Result<Self, QoiError> {
+        let header = data.get(..HEADER_LEN).ok_or(QoiError::Header)?;
+        let width = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+        let height = u32::from_be_bytes([header[8], header[9], header[10], header[11]]);
+        let channels = header[12];
+        if &header[..4] != MAGIC || !(3..=4).contains(&channels) || header[13] > 1 {
+            return Err(QoiError::Header);
+        }
+        Ok(QoiImage {
+            width,
+            height,
+            channels,
+            data: &data[HEADER_LEN..],
+        })
+    }
+
+    /// Get the width of the image
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height of the image
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get the number of channels of the source image, 3 for RGB or 4 for RGBA
+    pub fn channels(&self) -> u8 {
+        self.channels
+    }
+
+    /// Iterate over the decoded pixels as red, green, blue and alpha bytes, row by row. The
+    /// iterator ends early if the data is truncated.
+    pub fn pixels(&self) -> Pixels<'a> {
+        Pixels {
+            data: self.data,
+            remaining: self.width as usize * self.height as usize,
+            index: [[0; 4]; 64],
+            pixel: [0, 0, 0, 255],
+            run: 0,
+        }
+    }
+}
+
+/// Iterator over the decoded pixels of a [`QoiImage`]
+#[derive(Clone, Debug)]
+pub struct Pixels<'a> {
+    data: &'a [u8],
+    remaining: usize,
+    index: [[u8; 4]; 64],
+    pixel: [u8; 4],
+    run: u8,
+}
+
+impl Pixels<'_> {
+    /// Read the next `N` bytes of data
+    fn read<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let bytes = self.data.get(..N)?.try_into().ok()?;
+        self.data = &self.data[N..];
+        Some(bytes)
+    }
+
+    /// Decode the next chunk into the current pixel
+    fn decode(&mut self) -> Option<()> {
+        let [op] = self.read()?;
+        let [r, g, b, a] = self.pixel;
+        self.pixel = match op {
+            OP_RGB => {
+                let [r, g, b] = self.read()?;
+                [r, g, b, a]
+            }
+            OP_RGBA => self.read()?,
+            _ => match op & MASK {
+                OP_INDEX => self.index[op as usize],
+                OP_DIFF => [
+                    r.wrapping_add((op >> 4) & 3).wrapping_sub(2),
+                    g.wrapping_add((op >> 2) & 3).wrapping_sub(2),
+                    b.wrapping_add(op & 3).wrapping_sub(2),
+                    a,
+                ],
+                OP_LUMA => {
+                    let [next] = self.read()?;
+                    let dg = (op & 0x3F).wrapping_sub(32);
+                    [
+                        r.wrapping_add(dg).wrapping_add(next >> 4).wrapping_sub(8),
+                        g.wrapping_add(dg),
+                        b.wrapping_add(dg).wrapping_add(next & 0x0F).wrapping_sub(8),
+                        a,
+                    ]
+                }
+                _ => {
+                    // OP_RUN, the current pixel and up to 61 more
+                    self.run = op & !OP_RUN;
+                    self.pixel
+                }
+            },
+        };
+        let [r, g, b, a] = self.pixel;
+        let hash = r as usize * 3 + g as usize * 5 + b as usize * 7 + a as usize * 11;
+        self.index[hash % 64] = self.pixel;
+        Some(())
+    }
+}
+
+impl Iterator for Pixels<'_> {
+    type Item = [u8; 4];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        if self.run > 0 {
+            self.run -= 1;
+        } else if self.decode().is_none() {
+            self.remaining = 0;
+            return None;
+        }
+        self.remaining -= 1;
+        Some(self.pixel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dither::Dithering;
+    use crate::mock;
+    use crate::properties::{DisplayRotation, DisplaySize};
+
+    /// A 3x3 image using every op: the first pixel as OP_RGB, an OP_RUN of 2 stored with a bias
+    /// of 1, an OP_LUMA, an OP_DIFF, an OP_INDEX of the first pixel, an OP_RGBA, an OP_INDEX of
+    /// the OP_LUMA pixel and an OP_RUN of 1
+    const IMAGE: &[u8] = &[
+        b'q', b'o', b'i', b'f', 0, 0, 0, 3, 0, 0, 0, 3, 3, 0, // header
+        0xFE, 10, 20, 30,   // OP_RGB
+        0xC1, // OP_RUN, 2 pixels
+        0xA5, 0xA5, // OP_LUMA, dg +5, dr - dg +2, db - dg -3
+        0x5A, // OP_DIFF, dr -1, dg 0, db 0
+        0x09, // OP_INDEX, (10 * 3 + 20 * 5 + 30 * 7 + 255 * 11) % 64
+        0xFF, 1, 2, 3, 128,  // OP_RGBA
+        0x05, // OP_INDEX, (17 * 3 + 25 * 5 + 32 * 7 + 255 * 11) % 64
+        0xC0, // OP_RUN, 1 pixel
+        0, 0, 0, 0, 0, 0, 0, 1, // end marker
+    ];
+
+    const PIXELS: [[u8; 4]; 9] = [
+        [10, 20, 30, 255],
+        [10, 20, 30, 255],
+        [10, 20, 30, 255],
+        [17, 25, 32, 255],
+        [16, 25, 32, 255],
+        [10, 20, 30, 255],
+        [1, 2, 3, 128],
+        [17, 25, 32, 255],
+        [17, 25, 32, 255],
+    ];
+
+    fn rgb565([r, g, b, _]: [u8; 4]) -> u16 {
+        (r as u16 >> 3) << 11 | (g as u16 >> 2) << 5 | b as u16 >> 3
+    }
+
+    #[test]
+    fn header() {
+        let image = QoiImage::new(IMAGE).unwrap();
+        assert_eq!((image.width(), image.height(), image.channels()), (3, 3, 3));
+        assert!(matches!(QoiImage::new(&IMAGE[..13]), Err(QoiError::Header)));
+        let mut data = [0; 14];
+        data.copy_from_slice(&IMAGE[..14]);
+        data[12] = 2;
+        assert!(matches!(QoiImage::new(&data), Err(QoiError::Header)));
+    }
+
+    #[test]
+    fn ops() {
+        let image = QoiImage::new(IMAGE).unwrap();
+        let mut pixels = image.pixels();
+        for (i, expected) in PIXELS.iter().enumerate() {
+            assert_eq!(pixels.next().as_ref(), Some(expected), "pixel {}", i);
+        }
+        assert_eq!(pixels.next(), None);
+    }
+
+    #[test]
+    fn truncated() {
+        // Cut in the OP_LUMA, after the first three pixels
+        let image = QoiImage::new(&IMAGE[..20]).unwrap();
+        assert_eq!(image.pixels().count(), 3);
+    }
+
+    #[test]
+    fn draw_clipped() {
+        let mut display = mock::graphics(DisplaySize::Display128x128, DisplayRotation::Rotate0);
+        let image = QoiImage::new(IMAGE).unwrap();
+        // One column off the left and one row off the bottom of the screen
+        display.draw_qoi(-1, 126, &image, Dithering::None).unwrap();
+        #[cfg(feature = "buffered")]
+        display.flush();
+
+        let mock = mock::release(display);
+        for (i, &pixel) in PIXELS.iter().enumerate() {
+            let (x, y) = (i as i32 % 3 - 1, i as i32 / 3 + 126);
+            if x >= 0 && y < 128 {
+                assert_eq!(mock.get(x as u8, y as u8), rgb565(pixel), "pixel {}", i);
+            }
+        }
+        assert_eq!(mock.get(2, 126), 0);
+        assert_eq!(mock.get(0, 125), 0);
+        #[cfg(not(feature = "buffered"))]
+        assert_eq!(mock.pixels, 4);
+
+        // An image entirely off screen draws nothing
+        let mut display = mock::graphics(DisplaySize::Display128x128, DisplayRotation::Rotate0);
+        display.draw_qoi(128, 0, &image, Dithering::None).unwrap();
+        assert_eq!(mock::release(display).pixels, 0);
+
+        // Rows below the screen are never decoded, so a cut there is not noticed
+        let image = QoiImage::new(&IMAGE[..24]).unwrap();
+        let mut display = mock::graphics(DisplaySize::Display128x128, DisplayRotation::Rotate0);
+        display.draw_qoi(0, 126, &image, Dithering::None).unwrap();
+        assert!(matches!(
+            display.draw_qoi(0, 125, &image, Dithering::None),
+            Err(QoiError::Truncated)
+        ));
+    }
+}
@@ -0,0 +1,197 @@
+//! Run length encoded images
+//!
+//! An image is stored as runs of three bytes each: a count from 1 to 255 followed by a big endian
+//! Rgb565 colour, covering the image row by row. Flat colour areas such as splash screens shrink
+//! to a fraction of their raw size, and every run is drawn as a single repeated colour transfer.
+//!
+//! The same runs are used by the RLE pixels message of the [`stream`](../stream/index.html)
+//! protocol.
+
+use crate::error::Error;
+
+/// Run length encode Rgb565 pixels into `out`, returning the number of bytes written.
+/// Returns `Error::OutOfRange` if `out` is too short, see [`encoded_len`].
+pub fn encode(pixels: &[u16], out: &mut [u8]) -> Result<usize, Error> {
+    let mut len = 0;
+    let mut rest = pixels;
+    while let Some(&color) = rest.first() {
+        let count = run_len(rest);
+        let [high, low] = color.to_be_bytes();
+        out.get_mut(len..len + 3)
+            .ok_or(Error::OutOfRange)?
+            .copy_from_slice(&[count as u8, high, low]);
+        len += 3;
+        rest = &rest[count..];
+    }
+    Ok(len)
+}
+
+/// Get the number of bytes `encode` writes for `pixels`
+pub fn encoded_len(pixels: &[u16]) -> usize {
+    let mut len = 0;
+    let mut rest = pixels;
+    while !rest.is_empty() {
+        len += 3;
+        rest = &rest[run_len(rest)..];
+    }
+    len
+}
+
+/// Length of the run at the start of `pixels`
+fn run_len(pixels: &[u16]) -> usize {
+    pixels
+        .iter()
+        .take(u8::MAX as usize)
+        .take_while(|&&pixel| pixel == pixels[0])
+        .count()
+}
+
+/// A run length encoded image, e.g. converted at compile time with
+/// `include_image!(..., format = rle)` from the `macros` feature. Draw it with
+/// [`GraphicsMode::draw_rle_image`](../mode/graphics/struct.GraphicsMode.html#method.draw_rle_image).
+#[derive(Clone, Copy, Debug)]
+pub struct RleImage<'a> {
+    width: u32,
+    height: u32,
+    data: &'a [u8],
+}
+
+impl<'a> RleImage<'a> {
+    /// Create a `width` by `height` image from runs made by [`encode`]. Panics, or fails to
+    /// compile in a constant, if the runs don't cover the image exactly.
+    pub const fn new(width: u32, height: u32, data: &'a [u8]) -> Self {
+        let mut pixels = 0;
+        let mut i = 0;
+        while i < data.len() {
+            assert!(i + 3 <= data.len() && data[i] != 0);
+            pixels += data[i] as usize;
+            i += 3;
+        }
+        assert!(pixels == width as usize * height as usize);
+        RleImage {
+            width,
+            height,
+            data,
+        }
+    }
+
+    /// Get the width of the image
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height of the image
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get the encoded runs
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Iterate over the runs of the image as a count and an Rgb565 colour
+    pub fn runs(&self) -> Runs<'a> {
+        Runs {
+            data: self.data.chunks_exact(3),
+        }
+    }
+
+    /// Iterate over the decoded Rgb565 pixels of the image, row by row
+    pub fn pixels(&self) -> impl Iterator<Item = u16> + 'a {
+        self.runs()
+            .flat_map(|(count, color)| (0..count).map(move |_| color))
+    }
+}
+
+/// Iterator over the runs of an [`RleImage`]
+#[derive(Clone, Debug)]
+pub struct Runs<'a> {
+    data: core::slice::ChunksExact<'a, u8>,
+}
+
+impl Iterator for Runs<'_> {
+    type Item = (u8, u16);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.data
+            .next()
+            .map(|run| (run[0], u16::from_be_bytes([run[1], run[2]])))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::mock;
+    use crate::properties::{DisplayRotation, DisplaySize};
+    use std::vec::Vec;
+
+    /// A 4x3 image whose runs cross the rows
+    const PIXELS: [u16; 12] = [1, 1, 1, 2, 2, 2, 3, 3, 3, 4, 4, 5];
+
+    #[test]
+    fn encode_roundtrip() {
+        // Runs longer than 255 pixels are split
+        let mut pixels = Vec::new();
+        pixels.extend((0..300).map(|_| 0xF800));
+        pixels.extend([0x07E0, 0x001F, 0x001F]);
+        let mut out = [0; 16];
+        let len = encode(&pixels, &mut out).unwrap();
+        assert_eq!(len, encoded_len(&pixels));
+        assert_eq!(
+            out[..len],
+            [255, 0xF8, 0, 45, 0xF8, 0, 1, 0x07, 0xE0, 2, 0, 0x1F]
+        );
+        let image = RleImage::new(101, 3, &out[..len]);
+        assert!(image.pixels().eq(pixels.iter().copied()));
+
+        assert!(matches!(
+            encode(&pixels, &mut out[..len - 1]),
+            Err(Error::OutOfRange)
+        ));
+    }
+
+    #[test]
+    fn draw_clipped() {
+        let mut data = [0; 15];
+        let len = encode(&PIXELS, &mut data).unwrap();
+        let image = RleImage::new(4, 3, &data[..len]);
+
+        let mut display = mock::graphics(DisplaySize::Display128x128, DisplayRotation::Rotate0);
+        // Off the left, the right and the bottom of the screen
+        let positions = [(-1, 0), (126, 10), (20, 126)];
+        for &(x, y) in positions.iter() {
+            display.draw_rle_image(x, y, &image).unwrap();
+        }
+        #[cfg(feature = "buffered")]
+        display.flush();
+
+        let mock = mock::release(display);
+        let mut visible = 0;
+        for &(x, y) in positions.iter() {
+            for (i, &pixel) in PIXELS.iter().enumerate() {
+                let (px, py) = (x + i as i32 % 4, y + i as i32 / 4);
+                if (0..128).contains(&px) && (0..128).contains(&py) {
+                    assert_eq!(
+                        mock.get(px as u8, py as u8),
+                        pixel,
+                        "pixel {} at {}, {}",
+                        i,
+                        x,
+                        y
+                    );
+                    visible += 1;
+                }
+            }
+        }
+        assert_eq!(visible, 9 + 6 + 8);
+        assert_eq!(mock.get(3, 0), 0);
+        assert_eq!(mock.get(125, 10), 0);
+        assert_eq!(mock.get(20, 125), 0);
+        #[cfg(not(feature = "buffered"))]
+        assert_eq!(mock.pixels, visible);
+    }
+}
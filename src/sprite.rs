@@ -0,0 +1,181 @@
+//! Colour keyed sprites
+
+use crate::error::Error;
+
+/// A block of big endian Rgb565 pixels, stored row by row, in which pixels of the key colour are
+/// transparent. Draw it with
+/// [`GraphicsMode::draw_sprite`](../mode/graphics/struct.GraphicsMode.html#method.draw_sprite).
+#[derive(Clone, Copy, Debug)]
+pub struct Sprite<'a> {
+    data: &'a [u8],
+    width: u32,
+    height: u32,
+    key: u16,
+}
+
+impl<'a> Sprite<'a> {
+    /// Create a `width` by `height` sprite from big endian Rgb565 data in which pixels of colour
+    /// `key` are transparent. Returns `Error::OutOfRange` if `data` is shorter than the sprite.
+    pub fn new(data: &'a [u8], width: u32, height: u32, key: u16) -> Result<Self, Error> {
+        if data.len() < width as usize * height as usize * 2 {
+            return Err(Error::OutOfRange);
+        }
+        Ok(Sprite {
+            data,
+            width,
+            height,
+            key,
+        })
+    }
+
+    /// Get the width of the sprite
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height of the sprite
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get the transparent colour
+    pub fn key(&self) -> u16 {
+        self.key
+    }
+
+    /// Get the pixel data
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Get the colour of the pixel at `x`, `y`, or `None` if it is transparent or outside of
+    /// the sprite
+    pub fn pixel(&self, x: u32, y: u32) -> Option<u16> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let i = (y as usize * self.width as usize + x as usize) * 2;
+        let color = u16::from_be_bytes([self.data[i], self.data[i + 1]]);
+        if color == self.key {
+            None
+        } else {
+            Some(color)
+        }
+    }
+
+    /// Get the data of row `y`
+    pub(crate) fn row(&self, y: u32) -> &'a [u8] {
+        let stride = self.width as usize * 2;
+        let start = y as usize * stride;
+        &self.data[start..start + stride]
+    }
+
+    /// Iterate over the opaque runs of row `y` between columns `start` and `end` as
+    /// `(first column, number of pixels)`
+    pub(crate) fn opaque_runs(&self, y: u32, start: u32, end: u32) -> OpaqueRuns<'a> {
+        OpaqueRuns {
+            row: self.row(y),
+            key: self.key,
+            x: start,
+            end,
+        }
+    }
+}
+
+/// Iterator over the runs of non key colour pixels in a sprite row
+pub(crate) struct OpaqueRuns<'a> {
+    row: &'a [u8],
+    key: u16,
+    x: u32,
+    end: u32,
+}
+
+impl OpaqueRuns<'_> {
+    fn is_opaque(&self, x: u32) -> bool {
+        let i = x as usize * 2;
+        u16::from_be_bytes([self.row[i], self.row[i + 1]]) != self.key
+    }
+}
+
+impl Iterator for OpaqueRuns<'_> {
+    type Item = (u32, u32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.x < self.end && !self.is_opaque(self.x) {
+            self.x += 1;
+        }
+        if self.x >= self.end {
+            return None;
+        }
+        let start = self.x;
+        while self.x < self.end && self.is_opaque(self.x) {
+            self.x += 1;
+        }
+        Some((start, self.x - start))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::mock::{self, Mock};
+    use crate::properties::{DisplayRotation, DisplaySize};
+    use std::vec::Vec;
+
+    const KEY: u16 = 0xF81F;
+    const BACKGROUND: u16 = 0x7777;
+
+    /// A 4x2 sprite with transparent pixels between and around its opaque runs
+    const PIXELS: [u16; 8] = [1, KEY, 2, 3, KEY, KEY, 4, KEY];
+
+    fn data() -> [u8; 16] {
+        let mut data = [0; 16];
+        for (bytes, pixel) in data.chunks_exact_mut(2).zip(PIXELS.iter()) {
+            bytes.copy_from_slice(&pixel.to_be_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn opaque_runs() {
+        let data = data();
+        let sprite = Sprite::new(&data, 4, 2, KEY).unwrap();
+        let runs = |y, start, end| sprite.opaque_runs(y, start, end).collect::<Vec<_>>();
+        assert_eq!(runs(0, 0, 4), [(0, 1), (2, 2)]);
+        assert_eq!(runs(0, 1, 3), [(2, 1)]);
+        assert_eq!(runs(1, 0, 4), [(2, 1)]);
+        assert_eq!(runs(1, 0, 2), []);
+    }
+
+    #[test]
+    fn draw_keeps_key_pixels() {
+        let data = data();
+        let sprite = Sprite::new(&data, 4, 2, KEY).unwrap();
+        // The background is in the GDDRAM, or in the framebuffer in buffered mode
+        let mut mock = Mock::default();
+        mock.ram.fill(BACKGROUND);
+        let mut display =
+            mock::graphics_on(mock, DisplaySize::Display128x128, DisplayRotation::Rotate0);
+        #[cfg(feature = "buffered")]
+        display.blit_pixels(10, 20, 4, 2, &[BACKGROUND; 8]).unwrap();
+        display.draw_sprite(10, 20, &sprite).unwrap();
+        #[cfg(feature = "buffered")]
+        display.flush();
+
+        let mock = mock::release(display);
+        for (i, &pixel) in PIXELS.iter().enumerate() {
+            let (x, y) = (10 + i as u8 % 4, 20 + i as u8 / 4);
+            let expected = if pixel == KEY { BACKGROUND } else { pixel };
+            assert_eq!(mock.get(x, y), expected, "pixel {}", i);
+        }
+        #[cfg(not(feature = "buffered"))]
+        {
+            // Every opaque run gets a window of its own
+            let writes = mock.codes().iter().filter(|&&code| code == 0x5C).count();
+            assert_eq!(writes, 3);
+            assert_eq!(mock.pixels, 4);
+        }
+    }
+}
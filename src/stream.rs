@@ -0,0 +1,827 @@
+//! Framed protocol for streaming display updates over a byte link
+//!
+//! Every message is sent as a frame:
+//!
+//! | Bytes | Content                                                  |
+//! |-------|----------------------------------------------------------|
+//! | 1     | Start of frame, `0xA5`                                   |
+//! | 1     | Message type                                             |
+//! | 2     | Payload length, little endian                            |
+//! | n     | Payload                                                  |
+//! | 2     | CRC-16/CCITT-FALSE of type, length and payload, little endian |
+//!
+//! The messages are:
+//!
+//! | Type   | Message     | Payload                                                        |
+//! |--------|-------------|----------------------------------------------------------------|
+//! | `0x01` | Window      | x, y, width, height                                            |
+//! | `0x02` | Pixels      | big endian Rgb565 pixels                                       |
+//! | `0x03` | RLE pixels  | runs of a count from 1 to 255 and a big endian Rgb565 colour   |
+//! | `0x04` | Fill        | x, y, width, height, big endian Rgb565 colour                  |
+//! | `0x05` | Brightness  | brightness from 0 to 255                                       |
+//! | `0x06` | Sync        | frame number, little endian `u32`                              |
+//!
+//! Coordinates are in the rotated coordinates of the receiving display. Pixels fill the last
+//! window row by row and wrap around at its end like the controller does. A frame with a bad
+//! checksum or payload is dropped and the decoder waits for the next start of frame.
+//!
+//! The sender encodes messages with [`encode`], the receiver feeds the bytes it gets into a
+//! [`Receiver`] which applies them to a [`GraphicsMode`].
+
+use crate::error::Error;
+use crate::mode::graphics::GraphicsMode;
+use crate::rle;
+use display_interface::WriteOnlyDataCommand;
+
+/// Byte starting every frame
+pub const START_OF_FRAME: u8 = 0xA5;
+
+/// Bytes of a frame around its payload
+pub const FRAME_OVERHEAD: usize = 6;
+
+const WINDOW: u8 = 0x01;
+const PIXELS: u8 = 0x02;
+const RLE_PIXELS: u8 = 0x03;
+const FILL: u8 = 0x04;
+const BRIGHTNESS: u8 = 0x05;
+const SYNC: u8 = 0x06;
+
+/// Errors of the streaming protocol
+#[derive(Clone, Debug)]
+pub enum StreamError {
+    /// The checksum of a frame did not match its content
+    Checksum,
+    /// A payload is longer than the decoder buffer or the encoder output
+    Overflow,
+    /// A frame has an unknown type or a payload of the wrong length
+    Malformed,
+    /// Applying a message to the display failed
+    Display(Error),
+}
+
+impl From<Error> for StreamError {
+    fn from(error: Error) -> Self {
+        StreamError::Display(error)
+    }
+}
+
+/// A decoded message
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Message<'a> {
+    /// Set the window the following pixels are drawn into
+    Window {
+        /// Left column
+        x: u8,
+        /// Top row
+        y: u8,
+        /// Width in pixels
+        width: u8,
+        /// Height in pixels
+        height: u8,
+    },
+    /// Big endian Rgb565 pixels
+    Pixels(&'a [u8]),
+    /// Run length encoded pixels, pairs of a count and a big endian Rgb565 colour
+    RlePixels(&'a [u8]),
+    /// Fill a rectangle with a colour
+    Fill {
+        /// Left column
+        x: u8,
+        /// Top row
+        y: u8,
+        /// Width in pixels
+        width: u8,
+        /// Height in pixels
+        height: u8,
+        /// Rgb565 colour
+        color: u16,
+    },
+    /// Set the display brightness
+    Brightness(u8),
+    /// End of a frame, with its number
+    Sync(u32),
+}
+
+impl<'a> Message<'a> {
+    /// Parse a message from its type and payload
+    fn parse(kind: u8, payload: &'a [u8]) -> Result<Self, StreamError> {
+        let message = match (kind, payload) {
+            (WINDOW, &[x, y, width, height]) => Message::Window {
+                x,
+                y,
+                width,
+                height,
+            },
+            (PIXELS, pixels) if pixels.len() & 1 == 0 => Message::Pixels(pixels),
+            (RLE_PIXELS, runs) if runs.len() % 3 == 0 => Message::RlePixels(runs),
+            (FILL, &[x, y, width, height, high, low]) => Message::Fill {
+                x,
+                y,
+                width,
+                height,
+                color: u16::from_be_bytes([high, low]),
+            },
+            (BRIGHTNESS, &[brightness]) => Message::Brightness(brightness),
+            (SYNC, &[a, b, c, d]) => Message::Sync(u32::from_le_bytes([a, b, c, d])),
+            _ => return Err(StreamError::Malformed),
+        };
+        Ok(message)
+    }
+
+    /// Get the type and write the payload of the message to `out`, returning its length
+    fn payload(&self, out: &mut [u8]) -> Result<(u8, usize), StreamError> {
+        let mut write = |kind: u8, payload: &[u8]| {
+            out.get_mut(..payload.len())
+                .ok_or(StreamError::Overflow)?
+                .copy_from_slice(payload);
+            Ok((kind, payload.len()))
+        };
+        match *self {
+            Message::Window {
+                x,
+                y,
+                width,
+                height,
+            } => write(WINDOW, &[x, y, width, height]),
+            Message::Pixels(pixels) => write(PIXELS, pixels),
+            Message::RlePixels(runs) => write(RLE_PIXELS, runs),
+            Message::Fill {
+                x,
+                y,
+                width,
+                height,
+                color,
+            } => {
+                let [high, low] = color.to_be_bytes();
+                write(FILL, &[x, y, width, height, high, low])
+            }
+            Message::Brightness(brightness) => write(BRIGHTNESS, &[brightness]),
+            Message::Sync(frame) => write(SYNC, &frame.to_le_bytes()),
+        }
+    }
+}
+
+/// CRC-16/CCITT-FALSE, polynomial 0x1021, initial value 0xFFFF
+fn crc16(crc: u16, bytes: &[u8]) -> u16 {
+    bytes.iter().fold(crc, |crc, &byte| {
+        let mut crc = crc ^ (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+/// Encode a message as a frame into `out`, returning the length of the frame. The frame is
+/// [`FRAME_OVERHEAD`] bytes longer than the payload.
+pub fn encode(message: &Message, out: &mut [u8]) -> Result<usize, StreamError> {
+    if out.len() < FRAME_OVERHEAD {
+        return Err(StreamError::Overflow);
+    }
+    let (kind, len) = message.payload(&mut out[4..])?;
+    if len > u16::MAX as usize || out.len() < len + FRAME_OVERHEAD {
+        return Err(StreamError::Overflow);
+    }
+    out[0] = START_OF_FRAME;
+    out[1] = kind;
+    out[2..4].copy_from_slice(&(len as u16).to_le_bytes());
+    let crc = crc16(0xFFFF, &out[1..4 + len]);
+    out[4 + len..6 + len].copy_from_slice(&crc.to_le_bytes());
+    Ok(len + FRAME_OVERHEAD)
+}
+
+/// Run length encode Rgb565 pixels into the payload of a [`Message::RlePixels`] in `out`,
+/// returning its length
+pub fn encode_rle(pixels: &[u16], out: &mut [u8]) -> Result<usize, StreamError> {
+    rle::encode(pixels, out).map_err(|_| StreamError::Overflow)
+}
+
+#[derive(Clone, Copy)]
+enum State {
+    Start,
+    Header(usize),
+    Payload,
+    Checksum(usize),
+}
+
+/// Byte by byte frame decoder holding payloads of up to `N` bytes
+pub struct Decoder<const N: usize> {
+    state: State,
+    header: [u8; 3],
+    payload: [u8; N],
+    len: usize,
+    crc: [u8; 2],
+}
+
+impl<const N: usize> Default for Decoder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Decoder<N> {
+    /// Create a decoder waiting for the start of a frame
+    pub fn new() -> Self {
+        Decoder {
+            state: State::Start,
+            header: [0; 3],
+            payload: [0; N],
+            len: 0,
+            crc: [0; 2],
+        }
+    }
+
+    /// Feed the next byte of the stream. Returns the message once a frame is complete, or an
+    /// error if it is dropped.
+    pub fn push(&mut self, byte: u8) -> Option<Result<Message<'_>, StreamError>> {
+        match self.state {
+            State::Start => {
+                if byte == START_OF_FRAME {
+                    self.state = State::Header(0);
+                }
+            }
+            State::Header(i) => {
+                self.header[i] = byte;
+                if i < 2 {
+                    self.state = State::Header(i + 1);
+                    return None;
+                }
+                let len = u16::from_le_bytes([self.header[1], self.header[2]]) as usize;
+                if len > N {
+                    self.state = State::Start;
+                    return Some(Err(StreamError::Overflow));
+                }
+                self.len = 0;
+                self.state = if len == 0 {
+                    State::Checksum(0)
+                } else {
+                    State::Payload
+                };
+            }
+            State::Payload => {
+                self.payload[self.len] = byte;
+                self.len += 1;
+                if self.len == self.payload_len() {
+                    self.state = State::Checksum(0);
+                }
+            }
+            State::Checksum(i) => {
+                self.crc[i] = byte;
+                if i == 0 {
+                    self.state = State::Checksum(1);
+                    return None;
+                }
+                self.state = State::Start;
+                let payload = &self.payload[..self.len];
+                let crc = crc16(crc16(0xFFFF, &self.header), payload);
+                if crc != u16::from_le_bytes(self.crc) {
+                    return Some(Err(StreamError::Checksum));
+                }
+                return Some(Message::parse(self.header[0], payload));
+            }
+        }
+        None
+    }
+
+    fn payload_len(&self) -> usize {
+        u16::from_le_bytes([self.header[1], self.header[2]]) as usize
+    }
+}
+
+/// Applies the messages decoded from a stream to a [`GraphicsMode`], holding payloads of up to
+/// `N` bytes
+pub struct Receiver<const N: usize> {
+    decoder: Decoder<N>,
+    window: Window,
+}
+
+impl<const N: usize> Default for Receiver<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Receiver<N> {
+    /// Create a receiver, drawing into the whole display until a window is received
+    pub fn new() -> Self {
+        Receiver {
+            decoder: Decoder::new(),
+            window: Window::default(),
+        }
+    }
+
+    /// Decode `bytes` and apply every complete message to `display`. In buffered mode the
+    /// framebuffer is flushed on every sync message. Returns the number of the last sync
+    /// message received, if any. Dropped frames are reported with the first error, the rest of
+    /// `bytes` is still decoded.
+    pub fn feed<DI, RST>(
+        &mut self,
+        display: &mut GraphicsMode<DI, RST>,
+        bytes: &[u8],
+    ) -> Result<Option<u32>, StreamError>
+    where
+        DI: WriteOnlyDataCommand,
+    {
+        let mut sync = None;
+        let mut first_error = None;
+        for &byte in bytes {
+            let result = match self.decoder.push(byte) {
+                Some(Ok(message)) => self.window.apply(display, &message),
+                Some(Err(error)) => Err(error),
+                None => continue,
+            };
+            match result {
+                Ok(Some(frame)) => sync = Some(frame),
+                Ok(None) => {}
+                Err(error) => {
+                    first_error.get_or_insert(error);
+                }
+            }
+        }
+        match first_error {
+            Some(error) => Err(error),
+            None => Ok(sync),
+        }
+    }
+}
+
+/// The window pixels are drawn into and the position of the next pixel in it
+#[derive(Clone, Copy, Default)]
+struct Window {
+    x: u8,
+    y: u8,
+    width: u8,
+    height: u8,
+    cursor: usize,
+}
+
+impl Window {
+    /// Apply a message, returning the frame number of sync messages
+    fn apply<DI, RST>(
+        &mut self,
+        display: &mut GraphicsMode<DI, RST>,
+        message: &Message,
+    ) -> Result<Option<u32>, StreamError>
+    where
+        DI: WriteOnlyDataCommand,
+    {
+        match *message {
+            Message::Window {
+                x,
+                y,
+                width,
+                height,
+            } => self.open(display, x, y, width, height)?,
+            Message::Pixels(pixels) => {
+                let mut chunk = [0u16; 32];
+                for bytes in pixels.chunks(chunk.len() * 2) {
+                    let len = bytes.len() / 2;
+                    for (pixel, bytes) in chunk.iter_mut().zip(bytes.chunks_exact(2)) {
+                        *pixel = u16::from_be_bytes([bytes[0], bytes[1]]);
+                    }
+                    self.put(display, &chunk[..len])?;
+                }
+            }
+            Message::RlePixels(runs) => {
+                for run in runs.chunks_exact(3) {
+                    let color = u16::from_be_bytes([run[1], run[2]]);
+                    self.fill(display, run[0] as usize, color)?;
+                }
+            }
+            Message::Fill {
+                x,
+                y,
+                width,
+                height,
+                color,
+            } => {
+                self.open(display, x, y, width, height)?;
+                self.fill(display, width as usize * height as usize, color)?;
+            }
+            Message::Brightness(brightness) => display.set_brightness(brightness)?,
+            Message::Sync(frame) => {
+                #[cfg(feature = "buffered")]
+                display.flush();
+                return Ok(Some(frame));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Check and open a window
+    fn open<DI, RST>(
+        &mut self,
+        display: &mut GraphicsMode<DI, RST>,
+        x: u8,
+        y: u8,
+        width: u8,
+        height: u8,
+    ) -> Result<(), StreamError>
+    where
+        DI: WriteOnlyDataCommand,
+    {
+        let (display_width, display_height) = display.get_dimensions();
+        if width == 0
+            || height == 0
+            || x as u16 + width as u16 > display_width as u16
+            || y as u16 + height as u16 > display_height as u16
+        {
+            return Err(Error::OutOfRange.into());
+        }
+        *self = Window {
+            x,
+            y,
+            width,
+            height,
+            cursor: 0,
+        };
+        Ok(())
+    }
+
+    /// Draw `count` pixels of one colour
+    fn fill<DI, RST>(
+        &mut self,
+        display: &mut GraphicsMode<DI, RST>,
+        count: usize,
+        color: u16,
+    ) -> Result<(), StreamError>
+    where
+        DI: WriteOnlyDataCommand,
+    {
+        let chunk = [color; 32];
+        let mut remaining = count;
+        while remaining > 0 {
+            let len = remaining.min(chunk.len());
+            self.put(display, &chunk[..len])?;
+            remaining -= len;
+        }
+        Ok(())
+    }
+
+    /// Draw pixels at the cursor, wrapping around at the end of the window
+    fn put<DI, RST>(
+        &mut self,
+        display: &mut GraphicsMode<DI, RST>,
+        pixels: &[u16],
+    ) -> Result<(), StreamError>
+    where
+        DI: WriteOnlyDataCommand,
+    {
+        if self.width == 0 {
+            // No window received yet, draw into the whole display
+            let (width, height) = display.get_dimensions();
+            self.open(display, 0, 0, width, height)?;
+        }
+        let size = self.width as usize * self.height as usize;
+        let start = self.cursor;
+        self.cursor = (start + pixels.len()) % size;
+
+        // Other commands, e.g. a brightness change, may have come in between, so the window is
+        // continued at the cursor, which sends nothing when the controller is still there
+        #[cfg(not(feature = "buffered"))]
+        {
+            let (mut pixels, mut cursor) = (pixels, start);
+            while !pixels.is_empty() {
+                let len = display
+                    .continue_window(self.x, self.y, self.width, self.height, cursor)?
+                    .min(pixels.len());
+                display.write_pixels(&pixels[..len])?;
+                pixels = &pixels[len..];
+                cursor = (cursor + len) % size;
+            }
+        }
+
+        #[cfg(feature = "buffered")]
+        for (i, &color) in pixels.iter().enumerate() {
+            let index = (start + i) % size;
+            let x = self.x as u32 + (index % self.width as usize) as u32;
+            let y = self.y as u32 + (index / self.width as usize) as u32;
+            display.set_pixel(x, y, color);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{self, Mock};
+    use crate::properties::{DisplayRotation, DisplaySize};
+
+    const RED: u16 = 0xF800;
+    const BLUE: u16 = 0x001F;
+
+    /// Encode a message into a buffer of the exact frame length
+    fn frame(message: &Message) -> ([u8; 64], usize) {
+        let mut out = [0; 64];
+        let len = encode(message, &mut out).unwrap();
+        (out, len)
+    }
+
+    /// Push bytes into a decoder, checking each completed frame in turn
+    fn decode<const N: usize>(
+        decoder: &mut Decoder<N>,
+        bytes: &[u8],
+        mut check: impl FnMut(Result<Message, StreamError>),
+    ) -> usize {
+        let mut frames = 0;
+        for &byte in bytes {
+            if let Some(result) = decoder.push(byte) {
+                check(result);
+                frames += 1;
+            }
+        }
+        frames
+    }
+
+    fn display() -> GraphicsMode<Mock> {
+        mock::graphics(DisplaySize::Display128x128, DisplayRotation::Rotate0)
+    }
+
+    #[test]
+    fn messages_roundtrip() {
+        let pixels = [0x12, 0x34, 0x56, 0x78];
+        let runs = [3, 0xF8, 0x00];
+        let messages = [
+            Message::Window {
+                x: 1,
+                y: 2,
+                width: 3,
+                height: 4,
+            },
+            Message::Pixels(&pixels),
+            Message::RlePixels(&runs),
+            Message::Fill {
+                x: 5,
+                y: 6,
+                width: 7,
+                height: 8,
+                color: 0xABCD,
+            },
+            Message::Brightness(200),
+            Message::Sync(0x0102_0304),
+        ];
+        let mut decoder = Decoder::<16>::new();
+        for message in messages.iter() {
+            let (out, len) = frame(message);
+            assert_eq!(out[0], START_OF_FRAME);
+            let frames = decode(&mut decoder, &out[..len], |result| {
+                assert_eq!(result.unwrap(), *message);
+            });
+            assert_eq!(frames, 1);
+        }
+    }
+
+    #[test]
+    fn checksum_mismatch() {
+        let (mut out, len) = frame(&Message::Brightness(10));
+        out[4] ^= 1;
+        let mut decoder = Decoder::<16>::new();
+        let frames = decode(&mut decoder, &out[..len], |result| {
+            assert!(matches!(result, Err(StreamError::Checksum)));
+        });
+        assert_eq!(frames, 1);
+
+        // A corrupted checksum is dropped the same way
+        let (mut out, len) = frame(&Message::Brightness(10));
+        out[len - 1] ^= 0x80;
+        let frames = decode(&mut decoder, &out[..len], |result| {
+            assert!(matches!(result, Err(StreamError::Checksum)));
+        });
+        assert_eq!(frames, 1);
+    }
+
+    #[test]
+    fn length_over_capacity() {
+        let pixels = [0x11; 8];
+        let (out, len) = frame(&Message::Pixels(&pixels));
+        let mut decoder = Decoder::<4>::new();
+        let mut errors = 0;
+        decode(&mut decoder, &out[..len], |result| {
+            assert!(matches!(result, Err(StreamError::Overflow)));
+            errors += 1;
+        });
+        assert_eq!(errors, 1);
+
+        // The decoder waits for the next frame, which fits
+        let (out, len) = frame(&Message::Pixels(&pixels[..4]));
+        let frames = decode(&mut decoder, &out[..len], |result| {
+            assert_eq!(result.unwrap(), Message::Pixels(&pixels[..4]));
+        });
+        assert_eq!(frames, 1);
+
+        let mut out = [0; 8];
+        assert!(matches!(
+            encode(&Message::Pixels(&pixels), &mut out),
+            Err(StreamError::Overflow)
+        ));
+    }
+
+    #[test]
+    fn resync_after_garbage() {
+        let mut decoder = Decoder::<16>::new();
+        let mut bytes = [0; 64];
+        bytes[..5].copy_from_slice(&[0x00, 0x13, 0xFF, 0x5A, 0x42]);
+        let (out, len) = frame(&Message::Sync(7));
+        bytes[5..5 + len].copy_from_slice(&out[..len]);
+        let frames = decode(&mut decoder, &bytes[..5 + len], |result| {
+            assert_eq!(result.unwrap(), Message::Sync(7));
+        });
+        assert_eq!(frames, 1);
+
+        // A frame cut short swallows the start of the next one, which is dropped by its
+        // checksum, and decoding recovers with the frame after it
+        let (first, first_len) = frame(&Message::Brightness(1));
+        let (second, second_len) = frame(&Message::Brightness(2));
+        let frames = decode(&mut decoder, &first[..first_len - 2], |_| {});
+        assert_eq!(frames, 0);
+        let frames = decode(&mut decoder, &second[..second_len], |result| {
+            assert!(matches!(result, Err(StreamError::Checksum)));
+        });
+        assert_eq!(frames, 1);
+        let frames = decode(&mut decoder, &second[..second_len], |result| {
+            assert_eq!(result.unwrap(), Message::Brightness(2));
+        });
+        assert_eq!(frames, 1);
+    }
+
+    #[test]
+    fn zero_length_payload() {
+        let (out, len) = frame(&Message::Pixels(&[]));
+        assert_eq!(len, FRAME_OVERHEAD);
+        let mut decoder = Decoder::<0>::new();
+        let frames = decode(&mut decoder, &out[..len], |result| {
+            assert_eq!(result.unwrap(), Message::Pixels(&[]));
+        });
+        assert_eq!(frames, 1);
+
+        // Messages with a fixed payload are malformed without one
+        let mut out = [START_OF_FRAME, BRIGHTNESS, 0, 0, 0, 0];
+        let crc = crc16(0xFFFF, &out[1..4]);
+        out[4..].copy_from_slice(&crc.to_le_bytes());
+        let frames = decode(&mut decoder, &out, |result| {
+            assert!(matches!(result, Err(StreamError::Malformed)));
+        });
+        assert_eq!(frames, 1);
+    }
+
+    #[test]
+    fn receiver_draws_window() {
+        let mut display = display();
+        let mut receiver = Receiver::<64>::new();
+        let mut pixels = [0; 16];
+        for (i, bytes) in pixels.chunks_exact_mut(2).enumerate() {
+            bytes.copy_from_slice(&(0x0100 + i as u16).to_be_bytes());
+        }
+        let window = Message::Window {
+            x: 2,
+            y: 3,
+            width: 4,
+            height: 2,
+        };
+        for message in [window, Message::Pixels(&pixels)].iter() {
+            let (out, len) = frame(message);
+            // Split frames across calls like a byte link would
+            assert_eq!(receiver.feed(&mut display, &out[..3]).unwrap(), None);
+            assert_eq!(receiver.feed(&mut display, &out[3..len]).unwrap(), None);
+        }
+        let (out, len) = frame(&Message::Sync(9));
+        assert_eq!(receiver.feed(&mut display, &out[..len]).unwrap(), Some(9));
+
+        let mock = mock::release(display);
+        for i in 0..8 {
+            let (x, y) = (2 + i % 4, 3 + i / 4);
+            assert_eq!(mock.get(x, y), 0x0100 + i as u16);
+        }
+        assert_eq!(mock.get(6, 3), 0);
+        assert_eq!(mock.get(2, 5), 0);
+    }
+
+    #[test]
+    fn receiver_continues_window_after_commands() {
+        let mut display = display();
+        display.init().unwrap();
+        let mut receiver = Receiver::<64>::new();
+        let mut pixels = [0; 16];
+        for (i, bytes) in pixels.chunks_exact_mut(2).enumerate() {
+            bytes.copy_from_slice(&(0x0100 + i as u16).to_be_bytes());
+        }
+        let messages = [
+            Message::Window {
+                x: 2,
+                y: 3,
+                width: 4,
+                height: 2,
+            },
+            Message::Brightness(0x80),
+            Message::Pixels(&pixels[..6]),
+            Message::Brightness(0x40),
+            Message::Pixels(&pixels[6..12]),
+            Message::Pixels(&pixels[12..]),
+            Message::Sync(1),
+        ];
+        for message in messages.iter() {
+            let (out, len) = frame(message);
+            receiver.feed(&mut display, &out[..len]).unwrap();
+        }
+
+        let mock = mock::release(display);
+        for i in 0..8 {
+            let (x, y) = (2 + i % 4, 3 + i / 4);
+            assert_eq!(mock.get(x, y), 0x0100 + i as u16);
+        }
+        // No pixel went out as a command argument
+        for (command, args) in mock.commands.iter() {
+            match command {
+                0xC7 => assert_eq!(args.len(), 1),
+                0xC1 => assert_eq!(args.len(), 3),
+                _ => {}
+            }
+        }
+        #[cfg(not(feature = "buffered"))]
+        {
+            // Window, then the rest of the first row and the second row after the brightness
+            // change, and nothing for the pixels directly following
+            let codes = mock.codes();
+            let last = codes.iter().rposition(|&c| c == 0xC7).unwrap();
+            assert_eq!(codes[last + 1..], [0x15, 0x75, 0x5C, 0x15, 0x75, 0x5C]);
+        }
+    }
+
+    #[test]
+    fn receiver_wraps_rle_and_fills() {
+        let mut display = display();
+        let mut receiver = Receiver::<64>::new();
+        let mut runs = [0; 8];
+        let len = encode_rle(&[RED, RED, RED, BLUE, BLUE], &mut runs).unwrap();
+        let messages = [
+            Message::Window {
+                x: 10,
+                y: 10,
+                width: 2,
+                height: 2,
+            },
+            Message::RlePixels(&runs[..len]),
+            Message::Fill {
+                x: 20,
+                y: 30,
+                width: 3,
+                height: 2,
+                color: BLUE,
+            },
+            Message::Sync(1),
+        ];
+        for message in messages.iter() {
+            let (out, len) = frame(message);
+            receiver.feed(&mut display, &out[..len]).unwrap();
+        }
+
+        let mock = mock::release(display);
+        // The fifth pixel wraps around to the start of the window
+        assert_eq!(mock.get(10, 10), BLUE);
+        assert_eq!(mock.get(11, 10), RED);
+        assert_eq!(mock.get(10, 11), RED);
+        assert_eq!(mock.get(11, 11), BLUE);
+        for (x, y) in (20..23).flat_map(|x| (30..32).map(move |y| (x, y))) {
+            assert_eq!(mock.get(x, y), BLUE);
+        }
+        assert_eq!(mock.get(23, 30), 0);
+    }
+
+    #[test]
+    fn receiver_reports_first_error() {
+        let mut display = display();
+        let mut receiver = Receiver::<64>::new();
+        let mut bytes = [0; 64];
+        let (out, bad_len) = frame(&Message::Window {
+            x: 120,
+            y: 0,
+            width: 16,
+            height: 1,
+        });
+        bytes[..bad_len].copy_from_slice(&out[..bad_len]);
+        let (out, len) = frame(&Message::Fill {
+            x: 0,
+            y: 0,
+            width: 1,
+            height: 1,
+            color: RED,
+        });
+        bytes[bad_len..bad_len + len].copy_from_slice(&out[..len]);
+        let (out, sync_len) = frame(&Message::Sync(2));
+        bytes[bad_len + len..bad_len + len + sync_len].copy_from_slice(&out[..sync_len]);
+
+        let result = receiver.feed(&mut display, &bytes[..bad_len + len + sync_len]);
+        assert!(matches!(
+            result,
+            Err(StreamError::Display(Error::OutOfRange))
+        ));
+        // The messages after the dropped one are still applied
+        assert_eq!(mock::release(display).get(0, 0), RED);
+    }
+}